@@ -0,0 +1,41 @@
+// External imports
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+// Imports from Optimizely crate
+use optimizely::{decision::DecideOptions, Client};
+
+const FILE_PATH: &str = "../datafiles/sandbox.json";
+
+// Median time per decision, measured with `cargo bench --bench decide` on the same machine, run one after the other
+//
+// | flag              | before resolving rules (7dd863e) | after resolving rules | after sharing values |
+// |-------------------|----------------------------------|-----------------------|----------------------|
+// | buy_button        | 362 ns                           | 419 ns                | 343 ns               |
+// | qa_rollout        | 374 ns                           | 445 ns                | 343 ns               |
+// | sorting_algorithm | 396 ns                           | 468 ns                | 370 ns               |
+//
+// Besides the time, `tests/decide_allocations.rs` checks that a decision does not allocate
+
+fn decide(c: &mut Criterion) {
+    let client = Client::from_local_datafile(FILE_PATH).unwrap().initialize();
+
+    // Only measure the decision itself, not the event dispatcher
+    let decide_options = DecideOptions {
+        disable_decision_event: true,
+        exclude_variables: true,
+        ..DecideOptions::default()
+    };
+
+    // Flag with an experiment, flag with only a rollout and flag with variables
+    let mut group = c.benchmark_group("decide");
+    for flag_key in ["buy_button", "qa_rollout", "sorting_algorithm"] {
+        group.bench_with_input(BenchmarkId::from_parameter(flag_key), flag_key, |b, flag_key| {
+            let user_context = client.create_user_context("user123");
+            b.iter(|| user_context.decide_with_options(black_box(flag_key), &decide_options))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, decide);
+criterion_main!(benches);
@@ -1,17 +1,48 @@
 //! Entrypoint of the SDK
 
+// External imports
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "online")]
+use std::time::Duration;
+#[cfg(feature = "async")]
+use tokio::task::JoinHandle;
+
 // Imports from crate
-use crate::datafile::Datafile;
+use crate::datafile::{Datafile, OptimizelyConfig};
+#[cfg(feature = "async")]
+use crate::event_api::AsyncEventDispatcher;
+#[cfg(feature = "online")]
+use crate::event_api::{CloseReport, DispatcherStats, Event, EventDispatcher};
+use crate::notification::NotificationCenter;
 #[cfg(feature = "online")]
-use crate::event_api::EventDispatcher;
+use crate::notification::{LogEventNotification, Notification, NotificationType};
 
 // Relative imports of sub modules
+#[cfg(feature = "batch")]
+pub use batch::{BatchDecisions, BatchOptions};
+use config_manager::ConfigManager;
 pub use error::ClientError;
+use file_watcher::FileWatcher;
 pub use initialization::UninitializedClient;
-pub use user::{AttributeValue, UserAttributes, UserContext};
+use overrides::{FlagOverride, Overrides};
+#[cfg(feature = "online")]
+use startup::Refresher;
+#[cfg(feature = "online")]
+pub use startup::StartupOptions;
+pub use user::{deserialize_attributes, AttributeValue, UserAttributes, UserContext};
 
+#[cfg(feature = "async")]
+mod async_poller;
+#[cfg(feature = "batch")]
+mod batch;
+mod bucketing;
+mod config_manager;
 mod error;
+mod file_watcher;
 mod initialization;
+mod overrides;
+#[cfg(feature = "online")]
+mod startup;
 mod user;
 
 /// SDK client to use Optimizely Feature Experimentation
@@ -27,21 +58,32 @@ mod user;
 ///     .initialize();
 ///
 /// // Use methods of client struct
-/// let account_id = optimizely_client.datafile().account_id();
-/// let revision = optimizely_client.datafile().revision();
+/// // The datafile can be replaced at any time, so keep hold of the current one while reading from it
+/// let datafile = optimizely_client.datafile();
+/// let account_id = datafile.account_id();
+/// let revision = datafile.revision();
 /// let user_context = optimizely_client.create_user_context(user_id);
 ///
 /// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 pub struct Client {
-    datafile: Datafile,
+    config_manager: Arc<ConfigManager>,
     #[cfg(feature = "online")]
     event_dispatcher: Box<dyn EventDispatcher>,
+    #[cfg(feature = "async")]
+    async_event_dispatcher: Option<Arc<dyn AsyncEventDispatcher>>,
+    #[cfg(feature = "async")]
+    datafile_poller: Mutex<Option<JoinHandle<()>>>,
+    #[cfg(feature = "online")]
+    datafile_refresher: Mutex<Option<Refresher>>,
+    datafile_watcher: Mutex<Option<FileWatcher>>,
+    overrides: Option<Arc<Overrides>>,
+    overrides_watcher: Mutex<Option<FileWatcher>>,
 }
 
 impl Client {
     /// Create a new user context for a given user id
-    pub fn create_user_context<'a>(&'a self, user_id: &'a str) -> UserContext {
+    pub fn create_user_context<'a>(&'a self, user_id: &'a str) -> UserContext<'a> {
         // Create an empty set of user attributes
         let attributes = UserAttributes::new();
 
@@ -51,13 +93,48 @@ impl Client {
     /// Create a new user context for a given user id
     pub fn create_user_context_with_attributes<'a>(
         &'a self, user_id: &'a str, attributes: UserAttributes,
-    ) -> UserContext {
+    ) -> UserContext<'a> {
         UserContext::new(self, user_id, attributes)
     }
 
-    /// Get the datafile within the client
-    pub fn datafile(&self) -> &Datafile {
-        &self.datafile
+    /// Create a new user context for a given user id that does not borrow the client
+    ///
+    /// Requires the client to be wrapped in an `Arc`, which the user context holds on to.
+    pub fn create_owned_user_context<T: Into<String>>(self: &Arc<Self>, user_id: T) -> UserContext<'static> {
+        // Create an empty set of user attributes
+        let attributes = UserAttributes::new();
+
+        UserContext::new_owned(Arc::clone(self), user_id.into(), attributes)
+    }
+
+    /// Create a new user context for a given user id that does not borrow the client
+    ///
+    /// Requires the client to be wrapped in an `Arc`, which the user context holds on to.
+    pub fn create_owned_user_context_with_attributes<T: Into<String>>(
+        self: &Arc<Self>, user_id: T, attributes: UserAttributes,
+    ) -> UserContext<'static> {
+        UserContext::new_owned(Arc::clone(self), user_id.into(), attributes)
+    }
+
+    /// Get the current datafile of the client
+    ///
+    /// The datafile can be replaced while the client is in use, for example by a poller.
+    /// The returned datafile stays valid, but does not reflect any later updates.
+    pub fn datafile(&self) -> Arc<Datafile> {
+        self.config_manager.datafile()
+    }
+
+    /// Replace the datafile of the client
+    ///
+    /// Nothing happens if the new datafile has the same revision as the current datafile.
+    /// Otherwise the OPTIMIZELY_CONFIG_UPDATE listeners are notified and `true` is returned.
+    pub fn update_datafile(&self, datafile: Datafile) -> bool {
+        self.config_manager.update(datafile)
+    }
+
+    /// Create a read-only snapshot of the configuration within the datafile
+    pub fn optimizely_config(&self) -> OptimizelyConfig {
+        self.datafile().optimizely_config()
     }
 
     /// Get the event dispatcher within the client
@@ -65,4 +142,149 @@ impl Client {
     pub fn event_dispatcher(&self) -> &dyn EventDispatcher {
         &*self.event_dispatcher
     }
+
+    /// Get the notification center to add or remove notification listeners
+    pub fn notification_center(&self) -> &NotificationCenter {
+        self.config_manager.notification_center()
+    }
+
+    /// Stop downloading datafile updates, stop watching the local files and send all pending events
+    ///
+    /// Waits at most `timeout` for the event dispatcher, so a hanging request to the Event API does not block forever.
+    /// The returned report tells how many pending events were sent and how many were dropped.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use optimizely::Client;
+    /// use optimizely::event_api::BatchedEventDispatcher;
+    /// #
+    /// # let file_path = "../datafiles/sandbox.json";
+    ///
+    /// // Initialize Optimizely client with an event dispatcher that sends events in batches
+    /// let optimizely_client = Client::from_local_datafile(file_path)?
+    ///     .with_event_dispatcher(BatchedEventDispatcher::default())
+    ///     .initialize();
+    ///
+    /// // Make a decision, which sends a decision event
+    /// let decision = optimizely_client.create_user_context("user1").decide("buy_button");
+    ///
+    /// // Flush the event before shutting down
+    /// let report = optimizely_client.close(Duration::from_secs(5));
+    /// assert_eq!(report.sent + report.dropped, 1);
+    ///
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "online")]
+    pub fn close(&self, timeout: Duration) -> CloseReport {
+        self.stop_background_updates();
+
+        self.event_dispatcher.close(timeout)
+    }
+
+    /// Counters of the events handled by the event dispatcher, if it keeps track of them
+    ///
+    /// ```
+    /// use optimizely::Client;
+    /// use optimizely::event_api::BatchedEventDispatcher;
+    /// #
+    /// # let file_path = "../datafiles/sandbox.json";
+    ///
+    /// let optimizely_client = Client::from_local_datafile(file_path)?
+    ///     .with_event_dispatcher(BatchedEventDispatcher::default())
+    ///     .initialize();
+    ///
+    /// // Make a decision, which hands a decision event to the dispatcher
+    /// optimizely_client.create_user_context("user1").decide("buy_button");
+    ///
+    /// let stats = optimizely_client.event_dispatcher_stats().unwrap();
+    /// assert_eq!(stats.events_enqueued, 1);
+    ///
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "online")]
+    pub fn event_dispatcher_stats(&self) -> Option<DispatcherStats> {
+        self.event_dispatcher.stats()
+    }
+
+    /// Stop downloading datafile updates, stop watching the local files
+    /// and wait until the async event dispatcher has sent all events
+    ///
+    /// Events of decisions made after closing are not sent.
+    #[cfg(feature = "async")]
+    pub async fn close_async(&self) {
+        self.stop_background_updates();
+
+        if let Some(event_dispatcher) = &self.async_event_dispatcher {
+            event_dispatcher.close().await;
+        }
+    }
+
+    // Stop the tasks and threads that update the client, which is only needed when closing
+    #[cfg_attr(not(feature = "online"), allow(dead_code))]
+    fn stop_background_updates(&self) {
+        #[cfg(feature = "async")]
+        self.stop_datafile_poller();
+
+        #[cfg(feature = "online")]
+        self.stop_datafile_refresher();
+
+        for watcher in [&self.datafile_watcher, &self.overrides_watcher] {
+            let watcher = watcher
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .take();
+            if let Some(watcher) = watcher {
+                watcher.stop();
+            }
+        }
+    }
+
+    #[cfg(feature = "online")]
+    fn stop_datafile_refresher(&self) {
+        let refresher = self
+            .datafile_refresher
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+        if let Some(refresher) = refresher {
+            refresher.stop();
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn stop_datafile_poller(&self) {
+        let poller = self
+            .datafile_poller
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+        if let Some(poller) = poller {
+            poller.abort();
+        }
+    }
+
+    /// Get the local override of a flag, if overrides were loaded
+    pub(crate) fn flag_override(&self, flag_key: &str) -> Option<FlagOverride> {
+        self.overrides.as_ref()?.get(flag_key)
+    }
+
+    /// Send an event to the event dispatcher and notify the LOG_EVENT listeners
+    #[cfg(feature = "online")]
+    pub(crate) fn dispatch_event(&self, event: Event) {
+        let notification_center = self.notification_center();
+        if notification_center.has_listeners(NotificationType::LogEvent) {
+            let notification = Notification::LogEvent(LogEventNotification { event: &event });
+            notification_center.send(&notification);
+        }
+
+        self.event_dispatcher.send_event(event);
+    }
+}
+
+#[cfg(feature = "async")]
+impl Drop for Client {
+    fn drop(&mut self) {
+        // The poller would stop by itself at its next tick, but there is no need to wait for that
+        self.stop_datafile_poller();
+    }
 }
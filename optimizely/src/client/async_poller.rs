@@ -0,0 +1,116 @@
+// External imports
+use std::sync::Weak;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+use tokio::time::{self, MissedTickBehavior};
+
+// Imports from crate
+use crate::datafile::Datafile;
+
+// Imports from super
+use super::{initialization::DatafileSource, ConfigManager};
+
+/// Spawn a task on the current tokio runtime that downloads the datafile at a fixed interval
+///
+/// The task only holds a weak reference to the config manager, so it stops once the client is dropped.
+/// Returns `None` when not called from within a tokio runtime.
+pub(super) fn spawn(
+    source: DatafileSource, interval: Duration, config_manager: Weak<ConfigManager>,
+) -> Option<JoinHandle<()>> {
+    let handle = match Handle::try_current() {
+        Ok(handle) => handle,
+        Err(_) => {
+            log::warn!("Datafile polling requires a tokio runtime");
+            return None;
+        }
+    };
+
+    let task = handle.spawn(async move {
+        let mut ticker = time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        // The first tick completes immediately, but the datafile was just downloaded
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+
+            // Errors are logged and the next attempt is made at the next tick
+            let content = match source.fetch_async().await {
+                Ok(content) => content,
+                Err(report) => {
+                    log::error!("Failed to download datafile\n{report:?}");
+                    continue;
+                }
+            };
+
+            // Stop polling if the client has been dropped in the meantime
+            let config_manager = match config_manager.upgrade() {
+                Some(config_manager) => config_manager,
+                None => break,
+            };
+
+            match Datafile::build(&content) {
+                Ok(datafile) => {
+                    source.store(&content);
+                    config_manager.update(datafile);
+                }
+                Err(report) => log::error!("Downloaded datafile is invalid\n{report:?}"),
+            }
+        }
+    });
+
+    Some(task)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    use crate::notification::{Notification, NotificationType};
+    use crate::test_support::serve;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn poller_updates_datafile() {
+        let content = std::fs::read_to_string("../datafiles/sandbox.json").unwrap();
+        let config_manager = Arc::new(ConfigManager::new(Datafile::build(&content).unwrap()));
+
+        // Record the revisions of every update
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let listener_updates = Arc::clone(&updates);
+        config_manager.notification_center().add_listener(
+            NotificationType::OptimizelyConfigUpdate,
+            move |notification| {
+                if let Notification::OptimizelyConfigUpdate(update) = notification {
+                    listener_updates
+                        .lock()
+                        .unwrap()
+                        .push((update.old_revision, update.new_revision));
+                }
+            },
+        );
+
+        // Local stand-in for the CDN that serves a newer revision of the datafile
+        let newer_content = content.replace("\"revision\": \"73\"", "\"revision\": \"74\"");
+        let url = serve(move |_| ("200 OK", newer_content.clone()));
+
+        let source = DatafileSource::new(format!("{url}/datafiles/sdk_key.json"), None);
+        let poller = spawn(source, Duration::from_millis(20), Arc::downgrade(&config_manager)).unwrap();
+
+        // Wait for a couple of polls
+        time::sleep(Duration::from_millis(200)).await;
+
+        // Only the first poll changes the revision
+        assert_eq!(config_manager.datafile().revision(), 74);
+        assert_eq!(*updates.lock().unwrap(), [(73, 74)]);
+
+        // The poller stops once the config manager is dropped
+        drop(config_manager);
+        time::timeout(Duration::from_secs(1), poller)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
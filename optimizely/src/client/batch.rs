@@ -0,0 +1,152 @@
+//! Deciding a flag for many users in parallel
+
+// External imports
+use rayon::iter::{ParallelBridge, ParallelIterator};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+// Imports from crate
+use crate::decision::{DecideOptions, Decision};
+
+// Imports from super
+use super::{Client, UserAttributes};
+
+/// Options for `Client::decide_batch`
+///
+/// ```
+/// use std::sync::Arc;
+/// use optimizely::client::BatchOptions;
+///
+/// // Use a dedicated thread pool of four threads and include the reasons of each decision
+/// let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(4).build()?;
+/// let mut batch_options = BatchOptions {
+///     thread_pool: Some(Arc::new(thread_pool)),
+///     ..BatchOptions::default()
+/// };
+/// batch_options.decide_options.include_reasons = true;
+///
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct BatchOptions {
+    /// Options used for every single decision
+    ///
+    /// The `disable_decision_event` field is ignored, use `send_decision_events` instead.
+    pub decide_options: DecideOptions,
+
+    /// Whether decision events are sent for the decisions in the batch, which is off by default
+    pub send_decision_events: bool,
+
+    /// Thread pool to evaluate the decisions on, a dedicated thread pool is created for the batch if not set
+    ///
+    /// The threads of the pool wait while the buffer is full,
+    /// so a pool that is shared with other work is held up by a slow consumer.
+    pub thread_pool: Option<Arc<ThreadPool>>,
+
+    /// Number of decisions that can be waiting to be consumed before the evaluation pauses
+    pub buffer_size: usize,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        BatchOptions {
+            decide_options: DecideOptions::default(),
+            send_decision_events: false,
+            thread_pool: None,
+            buffer_size: 1024,
+        }
+    }
+}
+
+/// Iterator over the decisions of `Client::decide_batch`
+///
+/// Decisions are yielded as soon as they are made, so they are not in the same order as the users.
+/// Use `Decision::user_context` to find out which user a decision belongs to.
+/// Dropping the iterator stops the evaluation of any remaining users.
+pub struct BatchDecisions {
+    receiver: Receiver<Decision<'static>>,
+}
+
+impl Iterator for BatchDecisions {
+    type Item = Decision<'static>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl Client {
+    /// Decide a single flag for many users in parallel
+    ///
+    /// The users are read from the iterator on a background thread and evaluated on a thread pool.
+    /// Unlike `UserContext::decide`, no decision events are sent unless `BatchOptions::send_decision_events` is set.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use optimizely::{Client, client::BatchOptions};
+    /// #
+    /// # let file_path = "../datafiles/sandbox.json";
+    ///
+    /// // Initialize a shared Optimizely client
+    /// let optimizely_client = Arc::new(Client::from_local_datafile(file_path)?.initialize());
+    ///
+    /// // Users can come from any source, like a file or a database query
+    /// let users = (0..10_000).map(|i| (format!("user{i}"), optimizely::user_attributes! {}));
+    ///
+    /// let enabled_count = optimizely_client
+    ///     .decide_batch("buy_button", users, BatchOptions::default())
+    ///     .filter(|decision| decision.enabled())
+    ///     .count();
+    ///
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn decide_batch<K, I>(self: &Arc<Self>, flag_key: K, users: I, options: BatchOptions) -> BatchDecisions
+    where
+        K: Into<String>,
+        I: IntoIterator<Item = (String, UserAttributes)>,
+        I::IntoIter: Send + 'static,
+    {
+        let client = Arc::clone(self);
+        let flag_key = flag_key.into();
+        let users = users.into_iter();
+
+        // Bounded channel, so a slow consumer does not cause all decisions to be kept in memory
+        let (sender, receiver) = mpsc::sync_channel(options.buffer_size);
+
+        let decide_options = DecideOptions {
+            disable_decision_event: !options.send_decision_events,
+            ..options.decide_options
+        };
+
+        // Stop early once the receiving end has been dropped
+        let decide = move |(user_id, attributes)| {
+            let user_context = client.create_owned_user_context_with_attributes(user_id, attributes);
+            let decision = user_context
+                .decide_with_options(&flag_key, &decide_options)
+                .into_owned();
+            sender.send(decision).map_err(|_| ())
+        };
+
+        // The global rayon thread pool is not used, since its threads would be blocked by a full buffer
+        let thread_pool = match options.thread_pool {
+            Some(thread_pool) => Ok(thread_pool),
+            None => ThreadPoolBuilder::new()
+                .thread_name(|index| format!("optimizely-batch-{index}"))
+                .build()
+                .map(Arc::new),
+        };
+
+        // The background thread only drives the thread pool, and finishes once all users are decided
+        match thread_pool {
+            Ok(thread_pool) => thread::spawn(move || thread_pool.install(|| users.par_bridge().try_for_each(decide))),
+            Err(error) => {
+                log::warn!("Unable to create thread pool, deciding batch on a single thread: {error}");
+                thread::spawn(move || users.into_iter().try_for_each(decide))
+            }
+        };
+
+        BatchDecisions { receiver }
+    }
+}
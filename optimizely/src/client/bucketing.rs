@@ -0,0 +1,109 @@
+//! Assigning users to a bucket of an experiment
+
+/// Constant used for the hashing algorithm
+const HASH_SEED: u32 = 1;
+
+/// Ranges are specified between 0 and 10_000
+const MAX_OF_RANGE: f64 = 10_000_f64;
+
+/// Bucket value between 0 and 10_000 for a user within an experiment
+///
+/// The bucketing key is the concatenation of user id and experiment id.
+/// Instead of allocating that concatenation, both parts are fed into the hash one after another.
+pub(crate) fn bucket_value(user_id: &str, experiment_id: &str) -> u64 {
+    let hash_value = murmur3_32(&[user_id.as_bytes(), experiment_id.as_bytes()], HASH_SEED);
+
+    // Bring the hash into a range of 0 to 10_000
+    ((hash_value as f64) / (u32::MAX as f64) * MAX_OF_RANGE) as u64
+}
+
+/// Murmur3 (32-bit) hash of the concatenation of `parts`
+///
+/// Gives the same result as hashing the concatenated bytes, without building them in a buffer.
+fn murmur3_32(parts: &[&[u8]], seed: u32) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+
+    let mut hash = seed;
+    let mut length = 0_usize;
+
+    // Bytes that did not fill a complete block yet, carried over to the next part
+    let mut block = [0_u8; 4];
+    let mut filled = 0_usize;
+
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        block[filled] = *byte;
+        filled += 1;
+        length += 1;
+
+        if filled == 4 {
+            let k = u32::from_le_bytes(block)
+                .wrapping_mul(C1)
+                .rotate_left(15)
+                .wrapping_mul(C2);
+            hash ^= k;
+            hash = hash
+                .rotate_left(13)
+                .wrapping_mul(5)
+                .wrapping_add(0xe654_6b64);
+            filled = 0;
+        }
+    }
+
+    // Remaining tail of 1 to 3 bytes
+    if filled > 0 {
+        let mut k = 0_u32;
+        for (i, byte) in block[..filled].iter().enumerate() {
+            k |= (*byte as u32) << (8 * i);
+        }
+        hash ^= k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+    }
+
+    // Finalization mix, the length is taken modulo 2^32 like the reference implementation
+    hash ^= length as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^= hash >> 16;
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn same_hash_as_murmur3_crate() {
+        let user_ids = [
+            "",
+            "a",
+            "ab",
+            "abc",
+            "abcd",
+            "user1",
+            "user123",
+            "123abc789xyz",
+            "ünïcødé",
+        ];
+        let experiment_ids = ["", "1", "12", "123", "9300000125242", "9300000133039"];
+
+        for user_id in user_ids {
+            for experiment_id in experiment_ids {
+                let bucketing_key = format!("{user_id}{experiment_id}");
+                let expected = murmur3::murmur3_32(&mut Cursor::new(&bucketing_key), HASH_SEED).unwrap();
+                let actual = murmur3_32(&[user_id.as_bytes(), experiment_id.as_bytes()], HASH_SEED);
+                assert_eq!(actual, expected, "bucketing_key={bucketing_key}");
+            }
+        }
+    }
+
+    #[test]
+    fn bucket_value_in_range() {
+        for i in 0..1_000 {
+            let user_id = format!("user{i}");
+            assert!(bucket_value(&user_id, "9300000125242") <= 10_000);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+// External imports
+use std::sync::{Arc, RwLock};
+
+// Imports from crate
+use crate::datafile::Datafile;
+use crate::notification::{ConfigUpdateNotification, Notification, NotificationCenter, NotificationType};
+
+/// Holds the current datafile of a client, which can be replaced while the client is in use
+///
+/// Pollers share the manager with the client through an `Arc`.
+/// Every decision works on the `Arc<Datafile>` that was current when it started,
+/// so replacing the datafile never affects a decision that is already being made.
+pub(crate) struct ConfigManager {
+    datafile: RwLock<Arc<Datafile>>,
+    notification_center: NotificationCenter,
+}
+
+impl ConfigManager {
+    pub(crate) fn new(datafile: Datafile) -> ConfigManager {
+        ConfigManager {
+            datafile: RwLock::new(Arc::new(datafile)),
+            notification_center: NotificationCenter::default(),
+        }
+    }
+
+    /// Get the current datafile
+    pub(crate) fn datafile(&self) -> Arc<Datafile> {
+        // A poisoned lock still holds a valid datafile, since replacing it cannot panic halfway
+        let datafile = self
+            .datafile
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Arc::clone(&datafile)
+    }
+
+    pub(crate) fn notification_center(&self) -> &NotificationCenter {
+        &self.notification_center
+    }
+
+    /// Replace the datafile if its revision differs from the current one
+    ///
+    /// Sends an OPTIMIZELY_CONFIG_UPDATE notification and returns true if the datafile was replaced.
+    pub(crate) fn update(&self, datafile: Datafile) -> bool {
+        self.swap(datafile, false)
+    }
+
+    /// Replace the datafile, even if it has the same revision as the current one
+    ///
+    /// Meant for a local datafile that was edited without changing its revision.
+    pub(crate) fn replace(&self, datafile: Datafile) {
+        self.swap(datafile, true);
+    }
+
+    fn swap(&self, datafile: Datafile, same_revision: bool) -> bool {
+        let new_revision = datafile.revision();
+
+        let new_datafile = Arc::new(datafile);
+        let old_datafile = {
+            let mut current = self
+                .datafile
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if current.revision() == new_revision && !same_revision {
+                log::debug!("Datafile is still at revision {new_revision}");
+                return false;
+            }
+            std::mem::replace(&mut *current, Arc::clone(&new_datafile))
+        };
+        let old_revision = old_datafile.revision();
+
+        match old_revision == new_revision {
+            true => log::info!("Replaced datafile without changing revision {new_revision}"),
+            false => log::info!("Updated datafile from revision {old_revision} to {new_revision}"),
+        }
+
+        // Listeners are called after releasing the lock, so they can use the new datafile
+        if self
+            .notification_center
+            .has_listeners(NotificationType::OptimizelyConfigUpdate)
+        {
+            let diff = old_datafile.diff(&new_datafile);
+            let notification = Notification::OptimizelyConfigUpdate(ConfigUpdateNotification {
+                old_revision,
+                new_revision,
+                diff: &diff,
+            });
+            self.notification_center.send(&notification);
+        }
+
+        true
+    }
+}
@@ -22,4 +22,7 @@ pub enum ClientError {
     #[doc(hidden)]
     #[error("Invalid Datafile")]
     InvalidDatafile,
+    #[doc(hidden)]
+    #[error("Failed to load flag overrides")]
+    InvalidOverrides,
 }
@@ -0,0 +1,65 @@
+// External imports
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Handle of a thread that watches a local file, the thread stops when the handle is dropped
+pub(crate) struct FileWatcher {
+    stop: Sender<()>,
+    thread: thread::JoinHandle<()>,
+}
+
+impl FileWatcher {
+    /// Stop the thread and wait until it is done
+    ///
+    /// Does not wait when called from the thread itself, for example by a listener that closes the client.
+    pub(crate) fn stop(self) {
+        drop(self.stop);
+        if self.thread.thread().id() != thread::current().id() {
+            let _ = self.thread.join();
+        }
+    }
+}
+
+// Modification time and size, since the modification time alone can be too coarse on some filesystems
+fn fingerprint(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Spawn a thread that checks a local file at a fixed interval and passes its content to `on_change` when it changed
+///
+/// The file is expected to be loaded already, so only changes after spawning are reported.
+/// The thread also stops when `on_change` returns false, for example because the client is gone.
+pub(crate) fn spawn<F>(path: PathBuf, interval: Duration, mut on_change: F) -> FileWatcher
+where
+    F: FnMut(String) -> bool + Send + 'static,
+{
+    let (stop, stopped) = mpsc::channel::<()>();
+    let mut last_fingerprint = fingerprint(&path);
+
+    // Dropping the handle disconnects the channel, which wakes up the thread immediately
+    let thread = thread::spawn(move || {
+        while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+            // A file that is missing for a moment, for example while it is replaced, is checked again at the next tick
+            let current_fingerprint = fingerprint(&path);
+            if current_fingerprint.is_none() || current_fingerprint == last_fingerprint {
+                continue;
+            }
+
+            match fs::read_to_string(&path) {
+                Ok(content) => {
+                    last_fingerprint = current_fingerprint;
+                    if !on_change(content) {
+                        break;
+                    }
+                }
+                Err(error) => log::warn!("Failed to read {}: {error}", path.display()),
+            }
+        }
+    });
+
+    FileWatcher { stop, thread }
+}
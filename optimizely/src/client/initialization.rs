@@ -2,14 +2,38 @@
 use error_stack::{IntoReport, Result, ResultExt};
 use std::fs::File;
 use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 // Imports from crate
-use crate::client::{Client, ClientError};
+use crate::client::{Client, ClientError, ConfigManager};
 use crate::datafile::Datafile;
 
+#[cfg(feature = "async")]
+use crate::event_api::{AsyncEventDispatcher, AsyncEventDispatcherAdapter};
 #[cfg(feature = "online")]
 use crate::event_api::{EventDispatcher, SimpleEventDispatcher};
 
+// Imports from super
+#[cfg(feature = "async")]
+use super::async_poller;
+#[cfg(feature = "online")]
+use super::startup::{self, StartupOptions};
+use super::{file_watcher, Overrides};
+
+// Location of the datafiles on the CDN
+#[cfg(feature = "online")]
+const DATAFILE_URL: &str = "https://cdn.optimizely.com/datafiles";
+#[cfg(feature = "online")]
+const AUTHENTICATED_DATAFILE_URL: &str = "https://config.optimizely.com/datafiles/auth";
+#[cfg(feature = "online")]
+const AUTHORIZATION_KEY: &str = "authorization";
+
+// Upper limit for a download of the datafile, so a hanging connection does not block a refresh forever
+#[cfg(feature = "online")]
+const DATAFILE_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// An intermediate struct that is returned when building a new Client
 ///
 /// ```
@@ -31,6 +55,87 @@ pub struct UninitializedClient {
     _user_profile_service: Option<()>,
     #[cfg(feature = "online")]
     event_dispatcher: Option<Box<dyn EventDispatcher>>,
+    #[cfg(feature = "online")]
+    datafile_source: Option<DatafileSource>,
+    #[cfg(feature = "online")]
+    refresh_interval: Option<Duration>,
+    #[cfg(feature = "async")]
+    polling_interval: Option<Duration>,
+    #[cfg(feature = "async")]
+    async_event_dispatcher: Option<Arc<dyn AsyncEventDispatcher>>,
+    overrides: Option<Overrides>,
+    overrides_reload_interval: Option<Duration>,
+    datafile_path: Option<PathBuf>,
+    watching_interval: Option<Duration>,
+}
+
+/// Location of a datafile on the CDN, kept around so the datafile can be downloaded again
+#[cfg(feature = "online")]
+#[derive(Clone)]
+pub(crate) struct DatafileSource {
+    url: String,
+    access_token: Option<String>,
+    cache_path: Option<PathBuf>,
+}
+
+#[cfg(feature = "online")]
+impl DatafileSource {
+    pub(crate) fn new(url: String, access_token: Option<&str>) -> DatafileSource {
+        DatafileSource {
+            url,
+            access_token: access_token.map(String::from),
+            cache_path: None,
+        }
+    }
+
+    /// Keep a copy of every valid datafile in a local file
+    pub(crate) fn with_cache_path(mut self, cache_path: Option<PathBuf>) -> DatafileSource {
+        self.cache_path = cache_path;
+        self
+    }
+
+    /// Read the last valid datafile from the cache, if there is one
+    pub(crate) fn load_cache(&self) -> Option<String> {
+        let cache_path = self.cache_path.as_ref()?;
+        match std::fs::read_to_string(cache_path) {
+            Ok(content) => Some(content),
+            Err(error) => {
+                log::warn!("Failed to read cached datafile {}: {error}", cache_path.display());
+                None
+            }
+        }
+    }
+
+    /// Store a valid datafile in the cache, replacing the previous one at once
+    pub(crate) fn store(&self, content: &str) {
+        let cache_path = match &self.cache_path {
+            Some(cache_path) => cache_path,
+            None => return,
+        };
+
+        // Write to a temporary file first, so the cache never contains half a datafile
+        let temporary_path = cache_path.with_extension("tmp");
+        let result =
+            std::fs::write(&temporary_path, content).and_then(|_| std::fs::rename(&temporary_path, cache_path));
+        if let Err(error) = result {
+            log::warn!("Failed to store datafile in cache {}: {error}", cache_path.display());
+        }
+    }
+
+    /// Download the datafile, blocking the current thread
+    pub(crate) fn fetch(&self) -> Result<String, ClientError> {
+        fetch_datafile(&self.url, self.access_token.as_deref())
+    }
+
+    /// Download the datafile on the blocking thread pool of the tokio runtime
+    #[cfg(feature = "async")]
+    pub(crate) async fn fetch_async(&self) -> Result<String, ClientError> {
+        let source = self.clone();
+        tokio::task::spawn_blocking(move || source.fetch())
+            .await
+            .into_report()
+            .change_context(ClientError::FailedRequest)?
+    }
 }
 
 impl Client {
@@ -38,23 +143,117 @@ impl Client {
     #[cfg(feature = "online")]
     pub fn from_sdk_key(sdk_key: &str) -> Result<UninitializedClient, ClientError> {
         // Construct URL
-        let url = format!("https://cdn.optimizely.com/datafiles/{}.json", sdk_key);
+        let source = DatafileSource::new(format!("{DATAFILE_URL}/{sdk_key}.json"), None);
 
         // Make GET request
-        // TODO: implement polling mechanism
-        let response = ureq::get(&url)
-            .call()
-            .into_report()
-            .change_context(ClientError::FailedRequest)?;
+        let content = source.fetch()?;
 
-        // Get response body
-        let content = response
-            .into_string()
-            .into_report()
-            .change_context(ClientError::FailedResponse)?;
+        // Use response to build Client
+        Client::from_string(&content).map(|client| client.with_datafile_source(source))
+    }
+
+    /// Download the datafile from the CDN using an SDK key, with a cache and fallback for when the CDN is unreachable
+    ///
+    /// Every valid datafile that is downloaded is stored in the cache.
+    /// When the download fails, the client starts from the cached datafile or else the fallback datafile.
+    /// Either way, the latest datafile is downloaded again in the background at `StartupOptions::refresh_interval`.
+    /// An error is only returned when none of these datafiles is available.
+    ///
+    /// Set `StartupOptions::access_token` to download the datafile of a secure environment.
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    /// use optimizely::client::StartupOptions;
+    /// use optimizely::Client;
+    /// #
+    /// # let sdk_key = "KVpGWnzPGKvvQ8yeEWmJZ";
+    ///
+    /// let options = StartupOptions {
+    ///     fallback_datafile: Some(include_str!("../../../datafiles/sandbox.json").to_owned()),
+    ///     cache_path: Some(PathBuf::from("/var/cache/optimizely/datafile.json")),
+    ///     ..StartupOptions::default()
+    /// };
+    /// let optimizely_client = Client::from_sdk_key_with_options(sdk_key, options)?
+    ///     .initialize();
+    ///
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "online")]
+    pub fn from_sdk_key_with_options(
+        sdk_key: &str, options: StartupOptions,
+    ) -> Result<UninitializedClient, ClientError> {
+        // Construct URL, which is different for secure environments
+        let source = match &options.access_token {
+            Some(access_token) => {
+                DatafileSource::new(format!("{AUTHENTICATED_DATAFILE_URL}/{sdk_key}.json"), Some(access_token))
+            }
+            None => DatafileSource::new(format!("{DATAFILE_URL}/{sdk_key}.json"), None),
+        };
+
+        // Make GET request, falling back to the cache or fallback datafile
+        startup::start(source, options)
+    }
+
+    /// Download the datafile from the CDN using an SDK key, without blocking the tokio runtime
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use optimizely::Client;
+    /// use optimizely::event_api::AsyncBatchedEventDispatcher;
+    /// #
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let sdk_key = "KVpGWnzPGKvvQ8yeEWmJZ";
+    /// // Initialize Optimizely client that downloads the latest datafile every five minutes
+    /// let optimizely_client = Client::from_sdk_key_async(sdk_key)
+    ///     .await?
+    ///     .with_async_polling(Duration::from_secs(300))
+    ///     .with_async_event_dispatcher(AsyncBatchedEventDispatcher::new())
+    ///     .initialize();
+    ///
+    /// // Stop polling and send all remaining events
+    /// optimizely_client.close_async().await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn from_sdk_key_async(sdk_key: &str) -> Result<UninitializedClient, ClientError> {
+        // Construct URL
+        let source = DatafileSource::new(format!("{DATAFILE_URL}/{sdk_key}.json"), None);
+
+        // Make GET request on a blocking thread
+        let content = source.fetch_async().await?;
+
+        // Use response to build Client
+        Client::from_string(&content).map(|client| client.with_datafile_source(source))
+    }
+
+    /// Download the datafile of a secure environment using an SDK key and a datafile access token
+    ///
+    /// The access token is sent as a bearer token and is never included in log messages or error reports.
+    ///
+    /// ```no_run
+    /// use optimizely::Client;
+    /// #
+    /// # let sdk_key = "KVpGWnzPGKvvQ8yeEWmJZ";
+    /// # let access_token = "datafile-access-token";
+    ///
+    /// // Initialize Optimizely client using the authenticated datafile
+    /// let optimizely_client = Client::from_sdk_key_with_token(sdk_key, access_token)?
+    ///     .initialize();
+    ///
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "online")]
+    pub fn from_sdk_key_with_token(sdk_key: &str, access_token: &str) -> Result<UninitializedClient, ClientError> {
+        // Construct URL
+        let url = format!("{AUTHENTICATED_DATAFILE_URL}/{sdk_key}.json");
+        let source = DatafileSource::new(url, Some(access_token));
+
+        // Make GET request with the token in the authorization header
+        let content = source.fetch()?;
 
         // Use response to build Client
-        Client::from_string(&content)
+        Client::from_string(&content).map(|client| client.with_datafile_source(source))
     }
 
     /// Read the datafile from the local filesystem
@@ -73,7 +272,7 @@ impl Client {
             .change_context(ClientError::FailedFileRead)?;
 
         // Use file content to build Client
-        Client::from_string(&content)
+        Client::from_string(&content).map(|client| client.with_datafile_path(file_path))
     }
 
     /// Use a string variable as the datafile
@@ -86,6 +285,36 @@ impl Client {
     }
 }
 
+/// Make GET request for the datafile, optionally authenticated with a bearer token
+#[cfg(feature = "online")]
+fn fetch_datafile(url: &str, access_token: Option<&str>) -> Result<String, ClientError> {
+    let mut request = ureq::get(url).timeout(DATAFILE_TIMEOUT);
+
+    match access_token {
+        Some(token) => {
+            // Only mention that a token is used, the token itself should never end up in the logs
+            log::debug!("Requesting authenticated datafile from {url}");
+            request = request.set(AUTHORIZATION_KEY, &format!("Bearer {token}"));
+        }
+        None => {
+            log::debug!("Requesting datafile from {url}");
+        }
+    }
+
+    // Errors of ureq only contain the URL and status, not the request headers
+    let response = request
+        .call()
+        .into_report()
+        .change_context(ClientError::FailedRequest)
+        .attach_printable_lazy(|| format!("url: {url}"))?;
+
+    // Get response body
+    response
+        .into_string()
+        .into_report()
+        .change_context(ClientError::FailedResponse)
+}
+
 impl UninitializedClient {
     pub(super) fn new(datafile: Datafile) -> UninitializedClient {
         UninitializedClient {
@@ -94,9 +323,41 @@ impl UninitializedClient {
             _user_profile_service: None,
             #[cfg(feature = "online")]
             event_dispatcher: None,
+            #[cfg(feature = "online")]
+            datafile_source: None,
+            #[cfg(feature = "online")]
+            refresh_interval: None,
+            #[cfg(feature = "async")]
+            polling_interval: None,
+            #[cfg(feature = "async")]
+            async_event_dispatcher: None,
+            overrides: None,
+            overrides_reload_interval: None,
+            datafile_path: None,
+            watching_interval: None,
         }
     }
 
+    // Remember where the datafile came from, so it can be polled for updates
+    #[cfg(feature = "online")]
+    pub(crate) fn with_datafile_source(mut self, source: DatafileSource) -> UninitializedClient {
+        self.datafile_source = Some(source);
+        self
+    }
+
+    // Keep downloading the datafile in the background, to pick up new revisions
+    #[cfg(feature = "online")]
+    pub(crate) fn with_datafile_refresh(mut self, interval: Duration) -> UninitializedClient {
+        self.refresh_interval = Some(interval);
+        self
+    }
+
+    // Remember which local file the datafile came from, so it can be watched for changes
+    fn with_datafile_path(mut self, file_path: &str) -> UninitializedClient {
+        self.datafile_path = Some(PathBuf::from(file_path));
+        self
+    }
+
     /// Use a custom event dispatcher
     #[cfg(feature = "online")]
     pub fn with_event_dispatcher(mut self, event_dispatcher: impl EventDispatcher + 'static) -> UninitializedClient {
@@ -104,17 +365,198 @@ impl UninitializedClient {
         self
     }
 
+    /// Use an event dispatcher that runs on the tokio runtime
+    ///
+    /// The dispatcher is flushed by `Client::close_async`.
+    #[cfg(feature = "async")]
+    pub fn with_async_event_dispatcher(
+        mut self, event_dispatcher: impl AsyncEventDispatcher + 'static,
+    ) -> UninitializedClient {
+        let event_dispatcher: Arc<dyn AsyncEventDispatcher> = Arc::new(event_dispatcher);
+        self.event_dispatcher = Some(Box::new(AsyncEventDispatcherAdapter(Arc::clone(&event_dispatcher))));
+        self.async_event_dispatcher = Some(event_dispatcher);
+        self
+    }
+
+    /// Download the datafile again at a fixed interval, using a task on the tokio runtime
+    ///
+    /// Only has effect for clients that were created from an SDK key.
+    /// The client has to be initialized within a tokio runtime, otherwise polling is not started.
+    #[cfg(feature = "async")]
+    pub fn with_async_polling(mut self, interval: Duration) -> UninitializedClient {
+        self.polling_interval = Some(interval);
+        self
+    }
+
+    /// Check the local datafile for changes at a fixed interval, using a background thread
+    ///
+    /// Only has effect for clients that were created from a local datafile.
+    /// A changed file replaces the datafile and notifies the OPTIMIZELY_CONFIG_UPDATE listeners.
+    /// This includes a file that was edited without changing its revision.
+    /// An invalid file is logged and the last valid datafile is kept.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use optimizely::Client;
+    /// #
+    /// # let file_path = "../datafiles/sandbox.json";
+    ///
+    /// // Initialize Optimizely client that picks up changes of the local datafile
+    /// let optimizely_client = Client::from_local_datafile(file_path)?
+    ///     .with_file_watching(Duration::from_secs(5))
+    ///     .initialize();
+    ///
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_file_watching(mut self, interval: Duration) -> UninitializedClient {
+        self.watching_interval = Some(interval);
+        self
+    }
+
+    /// Force the outcome of flags with a local JSON file, for example to try out a flag during development
+    ///
+    /// The file maps flag keys to an override with an optional variation key, enabled state and variable values.
+    /// Overridden flags do not look at the datafile for targeting or bucketing, and never send decision events.
+    /// Variables that are not overridden take their value from the variation with the same key, if the flag has one.
+    ///
+    /// ```json
+    /// {
+    ///     "buy_button": { "variation": "primary", "variables": { "color": "green" } },
+    ///     "qa_rollout": { "enabled": false }
+    /// }
+    /// ```
+    pub fn with_overrides_file(mut self, file_path: &str) -> Result<UninitializedClient, ClientError> {
+        self.overrides = Some(Overrides::load(Path::new(file_path))?);
+        Ok(self)
+    }
+
+    /// Check the overrides file for changes at a fixed interval, using a background thread
+    ///
+    /// Only has effect when an overrides file is used. An invalid file is logged and the previous overrides are kept.
+    pub fn with_overrides_reload(mut self, interval: Duration) -> UninitializedClient {
+        self.overrides_reload_interval = Some(interval);
+        self
+    }
+
     // TODO: implement with_default_decide_options and with_user_profile_service
 
     /// Initialize the client
     pub fn initialize(self) -> Client {
+        let config_manager = Arc::new(ConfigManager::new(self.datafile));
+
+        // Refresh a datafile that is possibly outdated
+        #[cfg(feature = "online")]
+        let datafile_refresher = match (self.refresh_interval, &self.datafile_source) {
+            (Some(interval), Some(source)) => {
+                Some(startup::spawn_refresh(source.clone(), interval, Arc::downgrade(&config_manager)))
+            }
+            _ => None,
+        };
+
+        // Start polling for new datafiles if asked for
+        #[cfg(feature = "async")]
+        let datafile_poller = match (self.polling_interval, self.datafile_source) {
+            (Some(interval), Some(source)) => async_poller::spawn(source, interval, Arc::downgrade(&config_manager)),
+            (Some(_), None) => {
+                log::warn!("Polling is only possible for a client that was created from an SDK key");
+                None
+            }
+            (None, _) => None,
+        };
+
+        // Reload the datafile when the file changes, until the client is dropped
+        let datafile_watcher = match (self.watching_interval, self.datafile_path) {
+            (Some(interval), Some(path)) => {
+                let weak_config_manager = Arc::downgrade(&config_manager);
+                Some(file_watcher::spawn(path, interval, move |content| {
+                    let config_manager = match weak_config_manager.upgrade() {
+                        Some(config_manager) => config_manager,
+                        None => return false,
+                    };
+                    // The file changed, so it is used even if someone forgot to change its revision
+                    match Datafile::build(&content) {
+                        Ok(datafile) => config_manager.replace(datafile),
+                        Err(report) => log::error!("Changed local datafile is invalid\n{report:?}"),
+                    }
+                    true
+                }))
+            }
+            (Some(_), None) => {
+                log::warn!("Watching is only possible for a client that was created from a local datafile");
+                None
+            }
+            (None, _) => None,
+        };
+
+        // Reload overrides when the file changes, until the client is dropped
+        let overrides = self.overrides.map(Arc::new);
+        let overrides_watcher = match (&overrides, self.overrides_reload_interval) {
+            (Some(overrides), Some(interval)) => {
+                let weak_overrides = Arc::downgrade(overrides);
+                Some(file_watcher::spawn(overrides.path().to_owned(), interval, move |content| {
+                    match weak_overrides.upgrade() {
+                        Some(overrides) => {
+                            overrides.reload(&content);
+                            true
+                        }
+                        None => false,
+                    }
+                }))
+            }
+            (None, Some(_)) => {
+                log::warn!("Reloading overrides is only possible when an overrides file is used");
+                None
+            }
+            (_, None) => None,
+        };
+
         // Select default for any options that were not specified
         Client {
-            datafile: self.datafile,
+            config_manager,
             #[cfg(feature = "online")]
             event_dispatcher: self
                 .event_dispatcher
                 .unwrap_or_else(|| Box::<SimpleEventDispatcher>::default()),
+            #[cfg(feature = "async")]
+            async_event_dispatcher: self.async_event_dispatcher,
+            #[cfg(feature = "async")]
+            datafile_poller: Mutex::new(datafile_poller),
+            #[cfg(feature = "online")]
+            datafile_refresher: Mutex::new(datafile_refresher),
+            datafile_watcher: Mutex::new(datafile_watcher),
+            overrides,
+            overrides_watcher: Mutex::new(overrides_watcher),
         }
     }
 }
+
+#[cfg(all(test, feature = "online"))]
+mod tests {
+    use super::*;
+    use crate::test_support::serve;
+    use std::sync::mpsc;
+
+    #[test]
+    fn fetch_datafile_redacts_token() {
+        let access_token = "secret-access-token";
+
+        // Local stand-in for the CDN that refuses every request
+        let (transmitter, requests) = mpsc::channel();
+        let url = serve(move |request| {
+            let _ = transmitter.send(request);
+            ("403 Forbidden", String::new())
+        });
+
+        let url = format!("{url}/datafiles/auth/sdk_key.json");
+        let report = fetch_datafile(&url, Some(access_token)).unwrap_err();
+        let request = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // The token should be sent to the server
+        assert_eq!(request.header("authorization"), Some("Bearer secret-access-token"));
+
+        // But never be part of the error report
+        assert_eq!(report.current_context(), &ClientError::FailedRequest);
+        assert!(!format!("{report:?}").contains(access_token));
+        assert!(!format!("{report:#}").contains(access_token));
+    }
+}
@@ -0,0 +1,76 @@
+// External imports
+use error_stack::{IntoReport, Result, ResultExt};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+// Imports from super
+use super::ClientError;
+
+/// Forced outcome of a single flag
+///
+/// Every field is optional, so an override can for example only turn a flag off.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FlagOverride {
+    pub(crate) variation: Option<String>,
+    pub(crate) enabled: Option<bool>,
+    #[serde(default)]
+    pub(crate) variables: Map<String, Value>,
+}
+
+/// Flag overrides from a local JSON file, which can be replaced while the client is in use
+pub(crate) struct Overrides {
+    path: PathBuf,
+    flags: RwLock<Arc<HashMap<String, FlagOverride>>>,
+}
+
+impl Overrides {
+    /// Read and parse the overrides file
+    pub(crate) fn load(path: &Path) -> Result<Overrides, ClientError> {
+        let content = std::fs::read_to_string(path)
+            .into_report()
+            .change_context(ClientError::InvalidOverrides)
+            .attach_printable_lazy(|| format!("path: {}", path.display()))?;
+
+        Ok(Overrides {
+            path: path.to_owned(),
+            flags: RwLock::new(Arc::new(parse(&content)?)),
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the override of a flag, if there is any
+    pub(crate) fn get(&self, flag_key: &str) -> Option<FlagOverride> {
+        let flags = self
+            .flags
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        flags.get(flag_key).cloned()
+    }
+
+    /// Replace the overrides with the new content of the file, or keep the current overrides if it is invalid
+    pub(crate) fn reload(&self, content: &str) {
+        match parse(content) {
+            Ok(flags) => {
+                log::info!("Reloaded {} flag overrides from {}", flags.len(), self.path.display());
+                *self
+                    .flags
+                    .write()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(flags);
+            }
+            Err(report) => log::error!("Keeping previous flag overrides\n{report:?}"),
+        }
+    }
+}
+
+fn parse(content: &str) -> Result<HashMap<String, FlagOverride>, ClientError> {
+    serde_json::from_str(content)
+        .into_report()
+        .change_context(ClientError::InvalidOverrides)
+}
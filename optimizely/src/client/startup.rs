@@ -0,0 +1,323 @@
+// External imports
+use error_stack::Result;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Weak;
+use std::thread;
+use std::time::Duration;
+
+// Imports from crate
+use crate::datafile::Datafile;
+
+// Imports from super
+use super::initialization::DatafileSource;
+use super::{Client, ClientError, ConfigManager, UninitializedClient};
+
+/// Options for `Client::from_sdk_key_with_options`, so a client can start while the CDN is unreachable
+///
+/// ```
+/// use std::time::Duration;
+/// use optimizely::client::StartupOptions;
+/// #
+/// # let fallback_datafile = std::fs::read_to_string("../datafiles/sandbox.json")?;
+///
+/// let options = StartupOptions {
+///     fallback_datafile: Some(fallback_datafile),
+///     cache_path: Some(std::env::temp_dir().join("optimizely-datafile.json")),
+///     refresh_interval: Duration::from_secs(10),
+///     access_token: None,
+/// };
+///
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone)]
+pub struct StartupOptions {
+    /// Datafile to start from when neither the CDN nor the cache is available, for example bundled with `include_str!`
+    pub fallback_datafile: Option<String>,
+    /// Local file that holds a copy of the last datafile that was downloaded
+    pub cache_path: Option<PathBuf>,
+    /// Time between downloads of the latest datafile in the background
+    pub refresh_interval: Duration,
+    /// Datafile access token of a secure environment, like the one of `Client::from_sdk_key_with_token`
+    pub access_token: Option<String>,
+}
+
+impl fmt::Debug for StartupOptions {
+    // The access token is left out, so it does not end up in the logs
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StartupOptions")
+            .field("fallback_datafile", &self.fallback_datafile.as_ref().map(|_| ".."))
+            .field("cache_path", &self.cache_path)
+            .field("refresh_interval", &self.refresh_interval)
+            .field("access_token", &self.access_token.as_ref().map(|_| "[redacted]"))
+            .finish()
+    }
+}
+
+impl Default for StartupOptions {
+    fn default() -> StartupOptions {
+        StartupOptions {
+            fallback_datafile: None,
+            cache_path: None,
+            refresh_interval: Duration::from_secs(60),
+            access_token: None,
+        }
+    }
+}
+
+/// Handle of a thread that keeps downloading the datafile, the thread stops when the handle is dropped
+pub(crate) struct Refresher {
+    stop: Sender<()>,
+    thread: thread::JoinHandle<()>,
+}
+
+impl Refresher {
+    /// Stop the thread and wait until it is done, which takes at most as long as a download
+    ///
+    /// Does not wait when called from the thread itself, for example by a listener that closes the client.
+    pub(crate) fn stop(self) {
+        drop(self.stop);
+        if self.thread.thread().id() != thread::current().id() {
+            let _ = self.thread.join();
+        }
+    }
+}
+
+/// Start from the CDN, or else from the cache, or else from the fallback datafile
+///
+/// In every case the datafile is downloaded again at the refresh interval, to pick up later revisions.
+pub(super) fn start(source: DatafileSource, options: StartupOptions) -> Result<UninitializedClient, ClientError> {
+    let source = source.with_cache_path(options.cache_path);
+
+    let report = match source.fetch() {
+        Ok(content) => match Client::from_string(&content) {
+            Ok(client) => {
+                source.store(&content);
+                return Ok(client
+                    .with_datafile_source(source)
+                    .with_datafile_refresh(options.refresh_interval));
+            }
+            Err(report) => report,
+        },
+        Err(report) => report,
+    };
+
+    // Any other datafile is possibly outdated, until the refresh downloads the latest one
+    log::warn!("Failed to download datafile, trying cache and fallback\n{report:?}");
+    let candidates = [
+        ("cached", source.load_cache()),
+        ("fallback", options.fallback_datafile),
+    ];
+    for (kind, content) in candidates {
+        let content = match content {
+            Some(content) => content,
+            None => continue,
+        };
+        match Client::from_string(&content) {
+            Ok(client) => {
+                log::info!("Starting from {kind} datafile");
+                return Ok(client
+                    .with_datafile_source(source)
+                    .with_datafile_refresh(options.refresh_interval));
+            }
+            Err(report) => log::warn!("The {kind} datafile is invalid\n{report:?}"),
+        }
+    }
+
+    // Without any datafile, the reason the download failed is the most relevant
+    Err(report)
+}
+
+/// Spawn a thread that downloads the datafile at a fixed interval, and caches every new revision
+pub(super) fn spawn_refresh(
+    source: DatafileSource, interval: Duration, config_manager: Weak<ConfigManager>,
+) -> Refresher {
+    let (stop, stopped) = mpsc::channel::<()>();
+
+    // Dropping the handle disconnects the channel, which wakes up the thread immediately
+    let thread = thread::spawn(move || {
+        while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+            let content = match source.fetch() {
+                Ok(content) => content,
+                Err(report) => {
+                    log::debug!("Datafile is unavailable\n{report:?}");
+                    continue;
+                }
+            };
+
+            let datafile = match Datafile::build(&content) {
+                Ok(datafile) => datafile,
+                Err(report) => {
+                    log::error!("Downloaded datafile is invalid\n{report:?}");
+                    continue;
+                }
+            };
+
+            // Stop if the client has been dropped in the meantime
+            let config_manager = match config_manager.upgrade() {
+                Some(config_manager) => config_manager,
+                None => break,
+            };
+
+            // The cache only needs to be written when the revision changed
+            if config_manager.update(datafile) {
+                source.store(&content);
+            }
+        }
+    });
+
+    Refresher { stop, thread }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{serve, temp_path};
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    const FILE_PATH: &str = "../datafiles/sandbox.json";
+
+    // Local stand-in for the CDN, which serves the current content of the datafile, if there is any
+    fn serve_datafile(content: Arc<Mutex<Option<String>>>) -> String {
+        let url = serve(move |_| match &*content.lock().unwrap() {
+            Some(content) => ("200 OK", content.clone()),
+            None => ("503 Service Unavailable", String::new()),
+        });
+        format!("{url}/datafiles/sdk_key.json")
+    }
+
+    fn cache_path(name: &str) -> PathBuf {
+        let path = temp_path("cache", name);
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn with_revision(revision: u32) -> String {
+        let content = std::fs::read_to_string(FILE_PATH).unwrap();
+        content.replace("\"revision\": \"73\"", &format!("\"revision\": \"{revision}\""))
+    }
+
+    fn wait_for_revision(client: &Client, revision: u32) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while client.datafile().revision() != revision {
+            assert!(Instant::now() < deadline, "datafile was not refreshed");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn downloaded_datafile_is_cached() {
+        let url = serve_datafile(Arc::new(Mutex::new(Some(with_revision(80)))));
+        let options = StartupOptions {
+            cache_path: Some(cache_path("downloaded")),
+            ..StartupOptions::default()
+        };
+
+        let client = start(DatafileSource::new(url, None), options.clone())
+            .unwrap()
+            .initialize();
+        assert_eq!(client.datafile().revision(), 80);
+
+        let cached = std::fs::read_to_string(options.cache_path.as_ref().unwrap()).unwrap();
+        assert_eq!(Datafile::build(&cached).unwrap().revision(), 80);
+
+        std::fs::remove_file(options.cache_path.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn start_from_cache_and_refresh() {
+        let content = Arc::new(Mutex::new(None));
+        let url = serve_datafile(Arc::clone(&content));
+        let options = StartupOptions {
+            fallback_datafile: Some(with_revision(1)),
+            cache_path: Some(cache_path("refresh")),
+            refresh_interval: Duration::from_millis(20),
+            access_token: None,
+        };
+        std::fs::write(options.cache_path.as_ref().unwrap(), with_revision(75)).unwrap();
+
+        // The cache takes precedence over the fallback
+        let client = start(DatafileSource::new(url, None), options.clone())
+            .unwrap()
+            .initialize();
+        assert_eq!(client.datafile().revision(), 75);
+
+        // Once the CDN is back, the latest datafile is used and cached
+        *content.lock().unwrap() = Some(with_revision(81));
+        wait_for_revision(&client, 81);
+        let cached = std::fs::read_to_string(options.cache_path.as_ref().unwrap()).unwrap();
+        assert_eq!(Datafile::build(&cached).unwrap().revision(), 81);
+
+        // Later revisions are picked up as well
+        *content.lock().unwrap() = Some(with_revision(82));
+        wait_for_revision(&client, 82);
+        let cached = std::fs::read_to_string(options.cache_path.as_ref().unwrap()).unwrap();
+        assert_eq!(Datafile::build(&cached).unwrap().revision(), 82);
+
+        std::fs::remove_file(options.cache_path.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn no_refresh_after_close() {
+        let content = Arc::new(Mutex::new(Some(with_revision(80))));
+        let url = serve_datafile(Arc::clone(&content));
+        let options = StartupOptions {
+            refresh_interval: Duration::from_millis(20),
+            ..StartupOptions::default()
+        };
+
+        let client = start(DatafileSource::new(url, None), options)
+            .unwrap()
+            .initialize();
+
+        // Closing stops the thread that downloads the datafile
+        client.close(Duration::from_secs(1));
+        *content.lock().unwrap() = Some(with_revision(81));
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(client.datafile().revision(), 80);
+    }
+
+    #[test]
+    fn start_from_fallback() {
+        let url = serve_datafile(Arc::new(Mutex::new(None)));
+        let options = StartupOptions {
+            fallback_datafile: Some(with_revision(1)),
+            cache_path: Some(cache_path("fallback")),
+            ..StartupOptions::default()
+        };
+
+        // An invalid cache is skipped
+        std::fs::write(options.cache_path.as_ref().unwrap(), "{").unwrap();
+
+        let client = start(DatafileSource::new(url, None), options.clone())
+            .unwrap()
+            .initialize();
+        assert_eq!(client.datafile().revision(), 1);
+
+        std::fs::remove_file(options.cache_path.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn without_any_datafile() {
+        let url = serve_datafile(Arc::new(Mutex::new(None)));
+
+        let report = start(DatafileSource::new(url, None), StartupOptions::default())
+            .err()
+            .unwrap();
+        assert_eq!(report.current_context(), &ClientError::FailedRequest);
+    }
+
+    #[test]
+    fn access_token_is_redacted() {
+        let options = StartupOptions {
+            access_token: Some(String::from("secret-access-token")),
+            ..StartupOptions::default()
+        };
+
+        let debug = format!("{options:?}");
+        assert!(!debug.contains("secret-access-token"));
+        assert!(debug.contains("[redacted]"));
+    }
+}
@@ -1,26 +1,40 @@
 // External imports
-use murmur3::murmur3_32 as murmur3_hash;
-use serde::Serialize;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
 use serde_json::value::Number;
+use serde_json::{Map, Value};
 use std::collections::HashMap;
-use std::io::Cursor;
+use std::ops::Deref;
+use std::sync::Arc;
 
 // Imports from crate
-use crate::datafile::{Experiment, FeatureFlag, Variation};
-use crate::decision::{DecideOptions, Decision};
+use crate::datafile::{Datafile, Experiment, FeatureFlag, Rule, Variation};
+use crate::decision::{DecideOptions, Decision, RuleType, UserContextSnapshot};
+#[cfg(feature = "online")]
+use crate::notification::TrackNotification;
+use crate::notification::{DecisionNotification, Notification, NotificationType};
 
 #[cfg(feature = "online")]
 use crate::event_api;
 
 // Imports from super
-use super::Client;
+use super::{bucketing, Client, FlagOverride};
 
+/// Value of a single user attribute
 #[derive(Clone, Debug, PartialEq)]
 pub enum AttributeValue {
+    /// Attribute without a value
     Null,
+    /// String attribute
     String(String),
+    /// Numeric attribute, either an integer or a finite float
     Number(Number),
+    /// Boolean attribute
     Bool(bool),
+    /// List of strings, for example the segments or tags of a user
+    ///
+    /// An `exact` condition matches when the list contains the value of the condition.
+    /// A `substring` condition matches when any element of the list contains the value of the condition.
+    List(Vec<String>),
 }
 
 impl From<bool> for AttributeValue {
@@ -35,12 +49,40 @@ impl From<&str> for AttributeValue {
     }
 }
 
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        AttributeValue::String(value)
+    }
+}
+
 impl From<i32> for AttributeValue {
     fn from(value: i32) -> Self {
         AttributeValue::Number(value.into())
     }
 }
 
+impl From<i64> for AttributeValue {
+    fn from(value: i64) -> Self {
+        AttributeValue::Number(value.into())
+    }
+}
+
+impl From<u64> for AttributeValue {
+    fn from(value: u64) -> Self {
+        AttributeValue::Number(value.into())
+    }
+}
+
+impl From<f32> for AttributeValue {
+    fn from(value: f32) -> Self {
+        // Go through the shortest decimal representation, so 0.1_f32 becomes 0.1 instead of 0.10000000149011612
+        match value.to_string().parse::<f64>() {
+            Ok(value) => value.into(),
+            Err(_) => f64::from(value).into(),
+        }
+    }
+}
+
 impl From<f64> for AttributeValue {
     fn from(value: f64) -> Self {
         if let Some(number) = Number::from_f64(value) {
@@ -51,6 +93,24 @@ impl From<f64> for AttributeValue {
     }
 }
 
+impl<T: Into<AttributeValue>> From<Option<T>> for AttributeValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(AttributeValue::Null, Into::into)
+    }
+}
+
+impl From<Vec<String>> for AttributeValue {
+    fn from(value: Vec<String>) -> Self {
+        AttributeValue::List(value)
+    }
+}
+
+impl From<Vec<&str>> for AttributeValue {
+    fn from(value: Vec<&str>) -> Self {
+        AttributeValue::List(value.into_iter().map(String::from).collect())
+    }
+}
+
 impl Serialize for AttributeValue {
     #[inline]
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -62,6 +122,30 @@ impl Serialize for AttributeValue {
             AttributeValue::Bool(value) => value.serialize(serializer),
             AttributeValue::String(value) => value.serialize(serializer),
             AttributeValue::Number(value) => value.serialize(serializer),
+            AttributeValue::List(value) => value.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AttributeValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Value::deserialize(deserializer)? {
+            Value::Null => Ok(AttributeValue::Null),
+            Value::Bool(value) => Ok(AttributeValue::Bool(value)),
+            Value::String(value) => Ok(AttributeValue::String(value)),
+            Value::Number(value) => Ok(AttributeValue::Number(value)),
+            Value::Array(values) => values
+                .into_iter()
+                .map(|value| match value {
+                    Value::String(value) => Ok(value),
+                    value => Err(D::Error::custom(format!("unsupported list element: {value}"))),
+                })
+                .collect::<Result<_, _>>()
+                .map(AttributeValue::List),
+            value => Err(D::Error::custom(format!("unsupported attribute value: {value}"))),
         }
     }
 }
@@ -91,6 +175,14 @@ impl AttributeValue {
             _ => None,
         }
     }
+    /// If the `Value` is a List, returns the associated strings. Returns
+    /// None otherwise.
+    pub fn as_list(&self) -> Option<&[String]> {
+        match self {
+            AttributeValue::List(list) => Some(list),
+            _ => None,
+        }
+    }
     /// Returns true if the `Value` is Null
     pub fn is_null(&self) -> bool {
         matches!(self, AttributeValue::Null)
@@ -98,13 +190,48 @@ impl AttributeValue {
 }
 
 /// Custom type alias for user attributes
+///
+/// Deserializing this map directly rejects the whole map when a single value is not supported,
+/// for example a nested object or a list of numbers.
+/// Use `deserialize_attributes` to skip those values instead.
 pub type UserAttributes = HashMap<String, AttributeValue>;
 
-/// Constant used for the hashing algorithm
-const HASH_SEED: u32 = 1;
-
-/// Ranges are specified between 0 and 10_000
-const MAX_OF_RANGE: f64 = 10_000_f64;
+/// Deserialize user attributes, skipping values that are not supported with a warning
+///
+/// This is how other Optimizely SDKs handle invalid attributes, so one bad value does not reject the whole request.
+///
+/// ```
+/// use serde::Deserialize;
+/// use optimizely::client::{deserialize_attributes, UserAttributes};
+///
+/// #[derive(Deserialize)]
+/// struct Request {
+///     user_id: String,
+///     #[serde(deserialize_with = "deserialize_attributes")]
+///     attributes: UserAttributes,
+/// }
+///
+/// let json = r#"{"user_id": "user123", "attributes": {"platform": "web", "nested": {"a": 1}}}"#;
+/// let request: Request = serde_json::from_str(json).unwrap();
+/// assert_eq!(request.attributes.len(), 1);
+/// ```
+pub fn deserialize_attributes<'de, D>(deserializer: D) -> Result<UserAttributes, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let values = HashMap::<String, Value>::deserialize(deserializer)?;
+    let attributes = values
+        .into_iter()
+        .filter_map(|(key, value)| match AttributeValue::deserialize(value) {
+            Ok(value) => Some((key, value)),
+            Err(error) => {
+                log::warn!("Ignoring attribute \"{key}\": {error}");
+                None
+            }
+        })
+        .collect();
+    Ok(attributes)
+}
 
 /// User specific context
 ///
@@ -134,19 +261,75 @@ const MAX_OF_RANGE: f64 = 10_000_f64;
 ///
 /// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
+///
+/// A user context created with `Client::create_user_context` borrows the client.
+/// When the client is wrapped in an `Arc`, an owned `UserContext<'static>` can be created instead.
+/// It can be stored, cloned and sent to other threads or across `await` points.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+/// use optimizely::{Client, decision::Decision};
+/// #
+/// # let file_path = "../datafiles/sandbox.json";
+///
+/// // Initialize a shared Optimizely client
+/// let optimizely_client = Arc::new(Client::from_local_datafile(file_path)?.initialize());
+///
+/// // The user context does not borrow anything
+/// let user_id = String::from("123abc789xyz");
+/// let user_context = optimizely_client.create_owned_user_context(user_id);
+///
+/// // So both the user context and its decision can be moved to another thread
+/// let handle = thread::spawn(move || -> Decision<'static> {
+///     user_context.decide("buy_button").into_owned()
+/// });
+/// let decision = handle.join().unwrap();
+///
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone)]
 pub struct UserContext<'a> {
-    client: &'a Client,
-    user_id: &'a str,
-    attributes: UserAttributes,
+    client: ClientRef<'a>,
+    // Both are shared with the decisions for this user, instead of copying them into every decision
+    user_id: Arc<str>,
+    attributes: Arc<UserAttributes>,
+}
+
+// Either a borrowed or a shared reference to the client
+#[derive(Clone)]
+enum ClientRef<'a> {
+    Borrowed(&'a Client),
+    Shared(Arc<Client>),
+}
+
+impl Deref for ClientRef<'_> {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        match self {
+            ClientRef::Borrowed(client) => client,
+            ClientRef::Shared(client) => client,
+        }
+    }
 }
 
 impl UserContext<'_> {
     // Only allow UserContext to be constructed from a Client
     pub(crate) fn new<'a>(client: &'a Client, user_id: &'a str, attributes: UserAttributes) -> UserContext<'a> {
         UserContext {
-            client,
-            user_id,
-            attributes,
+            client: ClientRef::Borrowed(client),
+            user_id: Arc::from(user_id),
+            attributes: Arc::new(attributes),
+        }
+    }
+
+    // Owned variant which keeps the client alive through reference counting
+    pub(crate) fn new_owned(client: Arc<Client>, user_id: String, attributes: UserAttributes) -> UserContext<'static> {
+        UserContext {
+            client: ClientRef::Shared(client),
+            user_id: Arc::from(user_id),
+            attributes: Arc::new(attributes),
         }
     }
 
@@ -155,13 +338,13 @@ impl UserContext<'_> {
         // Create owned copies of the key and value
         let key = key.into();
 
-        // Add the attribute
-        self.attributes.insert(key, value.into());
+        // Add the attribute, which only copies the attributes when a decision still shares them
+        Arc::make_mut(&mut self.attributes).insert(key, value.into());
     }
 
     /// Get the id of a user
     pub fn user_id(&self) -> &str {
-        self.user_id
+        &self.user_id
     }
 
     /// Get all attributes of a user
@@ -173,20 +356,32 @@ impl UserContext<'_> {
     #[cfg(feature = "online")]
     /// Track a conversion event for this user
     pub fn track_event(&self, event_key: &str) {
-        match self.client.datafile().event(event_key) {
+        let datafile = self.client.datafile();
+        match datafile.event(event_key) {
             Some(event) => {
                 log::debug!("Logging conversion event");
 
                 // Send out a decision event as a side effect
                 let user_id = self.user_id();
-                let account_id = self.client.datafile().account_id();
+                let account_id = datafile.account_id();
                 let event_id = event.id();
 
                 // Create event_api::Event to send to dispatcher
                 let conversion_event = event_api::Event::conversion(account_id, user_id, event_id, event_key);
 
                 // Ignore result of the send_decision function
-                self.client.event_dispatcher().send_event(conversion_event);
+                self.client.dispatch_event(conversion_event);
+
+                // Notify the TRACK listeners
+                let notification_center = self.client.notification_center();
+                if notification_center.has_listeners(NotificationType::Track) {
+                    let notification = Notification::Track(TrackNotification {
+                        user_id,
+                        attributes: &self.attributes,
+                        event_key,
+                    });
+                    notification_center.send(&notification);
+                }
             }
             None => {
                 log::warn!("Event key does not exist in datafile");
@@ -202,124 +397,295 @@ impl UserContext<'_> {
 
     /// Decide which variation to show to a user
     pub fn decide_with_options<'b>(&self, flag_key: &'b str, options: &DecideOptions) -> Decision<'b> {
+        let mut reasons = Reasons::new(options.include_reasons);
+
+        // Use the same datafile for the whole decision, even if it is replaced in the meantime
+        let datafile = self.client.datafile();
+
+        // Local overrides take precedence over the datafile, and never send a decision event
+        if let Some(flag_override) = self.client.flag_override(flag_key) {
+            reasons.add(|| format!("Flag \"{flag_key}\" is overridden by the local overrides file."));
+            let decision = self
+                .decide_override(&datafile, flag_key, &flag_override, options)
+                .with_reasons(reasons.into_vec());
+            self.notify_decision(&decision, false);
+            return decision;
+        }
+
         // Retrieve Flag object
-        let flag = match self.client.datafile().flag(flag_key) {
+        let flag = match datafile.flag(flag_key) {
             Some(flag) => flag,
             None => {
                 // When flag key cannot be found, return the off variation
                 // CONSIDERATION: Could have used Result<Decision, E> but this is how other Optimizely SDKs work
-                return Decision::off(flag_key);
+                reasons.add_critical(|| format!("No flag was found for key \"{flag_key}\"."));
+                let decision = Decision::off(flag_key, self.snapshot()).with_reasons(reasons.into_vec());
+                self.notify_decision(&decision, false);
+                return decision;
             }
         };
 
         // Only send decision events if the disable_decision_event option is false
         let send_decision = !options.disable_decision_event;
 
-        // Get the selected variation for the given flag
-        match self.decide_variation_for_flag(flag, send_decision) {
-            Some(variation) => {
+        // Get the selected rule and variation for the given flag
+        let result = self.decide_variation_for_flag(flag, &mut reasons);
+
+        // Decision events are always sent for experiments, but for rollouts only if the datafile asks for it
+        let is_experiment = matches!(result, Some((rule, _, _)) if rule.rule_type() == RuleType::FeatureTest);
+        let decision_event_dispatched =
+            cfg!(feature = "online") && send_decision && (is_experiment || datafile.send_flag_decisions());
+
+        #[cfg(feature = "online")]
+        if decision_event_dispatched {
+            let rule = result.map(|(rule, variation, _)| (rule.experiment().as_ref(), variation.as_ref()));
+            let rule_type = result.map_or(RuleType::Rollout, |(rule, _, _)| rule.rule_type());
+            self.send_decision_event(&datafile, flag_key, rule, rule_type);
+        }
+
+        // The keys and variables are shared with the datafile instead of copied
+        let (decision, variables) = match result {
+            Some((rule, variation, bucket)) => {
                 // Unpack the variation and create Decision struct
-                Decision::new(flag_key, variation.is_feature_enabled(), variation.key())
+                let variation_key = Arc::clone(variation.shared_key());
+                let decision = Decision::new(flag_key, variation.is_feature_enabled(), variation_key, self.snapshot())
+                    .with_rule_key(Some(Arc::clone(rule.experiment().shared_key())));
+                (decision, rule.variables(bucket))
             }
             None => {
                 // No experiment or rollout found, or user does not qualify for any
-                Decision::off(flag_key)
+                (Decision::off(flag_key, self.snapshot()), flag.default_variables())
             }
+        };
+
+        // Variables fall back to their default values when no variation was found
+        let decision = if options.exclude_variables {
+            decision
+        } else {
+            decision.with_variables(Arc::clone(variables))
+        };
+
+        let decision = decision.with_reasons(reasons.into_vec());
+
+        self.notify_decision(&decision, decision_event_dispatched);
+
+        decision
+    }
+
+    /// Decision for a flag that is overridden, with the variation and variables of the datafile as fallback
+    fn decide_override<'b>(
+        &self, datafile: &Datafile, flag_key: &'b str, flag_override: &FlagOverride, options: &DecideOptions,
+    ) -> Decision<'b> {
+        let flag = datafile.flag(flag_key);
+
+        // Look for a variation with the same key in any of the rules of the flag
+        let variation = match (flag, &flag_override.variation) {
+            (Some(flag), Some(variation_key)) => flag.rules().iter().find_map(|rule| {
+                rule.experiment()
+                    .variations()
+                    .values()
+                    .find(|variation| variation.key() == variation_key)
+            }),
+            _ => None,
+        };
+
+        let enabled = flag_override
+            .enabled
+            .or_else(|| variation.map(|variation| variation.is_feature_enabled()))
+            .unwrap_or(true);
+        let variation_key = match &flag_override.variation {
+            Some(variation_key) => variation_key.as_str(),
+            None if enabled => "on",
+            None => "off",
+        };
+
+        let decision = Decision::new(flag_key, enabled, variation_key, self.snapshot());
+        if options.exclude_variables {
+            return decision;
         }
+
+        let mut variables = flag.map_or_else(Map::new, |flag| flag.variable_values(variation.map(Arc::as_ref)));
+        variables.extend(flag_override.variables.clone());
+        decision.with_variables(Arc::new(variables))
     }
 
-    fn decide_variation_for_flag<'a>(&'a self, flag: &'a FeatureFlag, send_decision: bool) -> Option<&Variation> {
-        // Find first Experiment for which this user qualifies
-        let result = flag.experiments_ids().iter().find_map(|experiment_id| {
-            let experiment = self.client.datafile().experiment(experiment_id);
+    /// Snapshot of the user id and attributes that a decision shares instead of copying
+    fn snapshot(&self) -> UserContextSnapshot {
+        UserContextSnapshot::new(&self.user_id, &self.attributes)
+    }
 
-            match experiment {
-                Some(experiment) => self.decide_variation_for_experiment(experiment, send_decision),
-                None => None,
-            }
-        });
+    /// Notify the DECIDE listeners
+    fn notify_decision(&self, decision: &Decision, decision_event_dispatched: bool) {
+        let notification_center = self.client.notification_center();
+        if notification_center.has_listeners(NotificationType::Decision) {
+            let notification = Notification::Decision(DecisionNotification {
+                user_id: &self.user_id,
+                attributes: &self.attributes,
+                flag_key: decision.flag_key(),
+                enabled: decision.enabled(),
+                variation_key: decision.variation_key(),
+                rule_key: decision.rule_key(),
+                variables: decision.variables(),
+                decision_event_dispatched,
+            });
+            notification_center.send(&notification);
+        }
+    }
+
+    /// Returns the matching rule, the selected variation and the bucket of that variation
+    fn decide_variation_for_flag<'a>(
+        &self, flag: &'a FeatureFlag, reasons: &mut Reasons,
+    ) -> Option<(&'a Rule, &'a Arc<Variation>, usize)> {
+        let user_id = self.user_id();
 
-        match result {
-            Some(_) => {
-                // A matching A/B test was found, send out any decisions
-                result
+        // Experiment rules come first, followed by the rules of the rollout
+        flag.rules().iter().find_map(|rule| {
+            let experiment = rule.experiment();
+            let rule_type = rule.rule_type();
+            let rule_key = experiment.key();
+
+            if !experiment.evaluate_audiences(&self.attributes) {
+                reasons.add(|| match rule_type {
+                    RuleType::Rollout => {
+                        format!("User \"{user_id}\" does not meet conditions for targeting rule \"{rule_key}\".")
+                    }
+                    _ => format!("User \"{user_id}\" does not meet conditions for experiment \"{rule_key}\"."),
+                });
+                return None;
             }
-            None => {
-                // No direct experiment found, let's look at the Rollout
-                let rollout = self.client.datafile().rollout(flag.rollout_id()).unwrap(); // TODO: remove unwrap
-
-                // Find the first experiment within the Rollout for which this user qualifies
-                rollout
-                    .experiments()
-                    .iter()
-                    .find_map(|experiment| self.decide_variation_for_experiment(experiment, false))
+
+            match self.bucket(experiment) {
+                Some((variation, bucket)) => {
+                    let variation_key = variation.key();
+                    reasons.add(|| match rule_type {
+                        RuleType::Rollout => format!("User \"{user_id}\" bucketed into targeting rule \"{rule_key}\"."),
+                        _ => format!(
+                            "User \"{user_id}\" is in variation \"{variation_key}\" of experiment \"{rule_key}\"."
+                        ),
+                    });
+                    Some((rule, variation, bucket))
+                }
+                None => {
+                    reasons.add(|| match rule_type {
+                        RuleType::Rollout => {
+                            format!("User \"{user_id}\" is not in the traffic of targeting rule \"{rule_key}\".")
+                        }
+                        _ => format!("User \"{user_id}\" is not in any variation of experiment \"{rule_key}\"."),
+                    });
+                    None
+                }
             }
-        }
+        })
     }
 
+    /// Decide which variation of a single experiment the user is bucketed into, if any
     pub fn decide_variation_for_experiment<'a>(
         &'a self, experiment: &'a Experiment, send_decision: bool,
-    ) -> Option<&Variation> {
+    ) -> Option<&'a Variation> {
         if !self.is_in_audience_of(experiment) {
             return None;
         }
-        // Use references for the ids
-        let user_id = self.user_id();
-        let experiment_id = experiment.id();
 
-        // Concatenate user id and experiment id
-        let bucketing_key = format!("{user_id}{experiment_id}");
+        let (variation, _) = self.bucket(experiment)?;
 
-        // To hash the bucket key it needs to be converted to an array of `u8` bytes
-        // Use Murmur3 (32-bit) with seed
-        let hash_value = match murmur3_hash(&mut Cursor::new(&bucketing_key), HASH_SEED) {
-            Ok(value) => value,
-            Err(_e) => {
-                log::warn!("Unable to create hash for bucketing_key={}", &bucketing_key);
-                return None;
+        if send_decision {
+            // Send out a decision event as a side effect, without a flag
+            #[cfg(feature = "online")]
+            self.send_decision_event(&self.client.datafile(), "", Some((experiment, variation)), RuleType::Experiment);
+        }
+
+        Some(variation)
+    }
+
+    /// Assign the user to a variation according to the traffic allocation of an experiment
+    fn bucket<'a>(&self, experiment: &'a Experiment) -> Option<(&'a Arc<Variation>, usize)> {
+        // Hash user id and experiment id into a value between 0 and 10_000
+        let bucket_value = bucketing::bucket_value(self.user_id(), experiment.id());
+
+        // Get the bucket according to the traffic allocation, and the variation of that bucket
+        let bucket = experiment.bucket(bucket_value)?;
+        Some((experiment.bucket_variation(bucket)?, bucket))
+    }
+
+    /// Send a decision event for the matching rule and variation of a flag, or for the "off" result without a rule
+    #[cfg(feature = "online")]
+    fn send_decision_event(
+        &self, datafile: &Datafile, flag_key: &str, rule: Option<(&Experiment, &Variation)>, rule_type: RuleType,
+    ) {
+        let account_id = datafile.account_id();
+
+        // Without a rule all IDs are left empty
+        let (campaign_id, experiment_id, variation_id, metadata) = match rule {
+            Some((experiment, variation)) => {
+                let metadata = event_api::DecisionMetadata {
+                    flag_key: flag_key.into(),
+                    rule_key: experiment.key().into(),
+                    rule_type,
+                    variation_key: variation.key().into(),
+                    enabled: variation.is_feature_enabled(),
+                };
+                (experiment.campaign_id(), experiment.id(), variation.id(), metadata)
+            }
+            None => {
+                let metadata = event_api::DecisionMetadata {
+                    flag_key: flag_key.into(),
+                    rule_key: String::new(),
+                    rule_type,
+                    variation_key: String::new(),
+                    enabled: false,
+                };
+                ("", "", "", metadata)
             }
         };
 
-        // Bring the hash into a range of 0 to 10_000
-        let bucket_value = ((hash_value as f64) / (u32::MAX as f64) * MAX_OF_RANGE) as u64;
-
-        // Get the variation according to the traffic allocation
-        let result = experiment.traffic_allocation().variation(bucket_value);
+        // Create event_api::Event to send to dispatcher
+        let decision_event = event_api::Event::flag_decision(
+            account_id,
+            &self.user_id,
+            campaign_id,
+            experiment_id,
+            variation_id,
+            metadata,
+        );
+
+        // Ignore result of the send_decision function
+        self.client.dispatch_event(decision_event);
+    }
 
-        match result {
-            Some(variation_id) => {
-                if send_decision {
-                    #[cfg(feature = "online")]
-                    {
-                        // Send out a decision event as a side effect
-                        let account_id = self.client.datafile().account_id();
-                        let campaign_id = experiment.campaign_id();
+    /// Whether the user attributes satisfy the audience conditions of an experiment
+    pub fn is_in_audience_of(&self, experiment: &Experiment) -> bool {
+        experiment.evaluate_audiences(&self.attributes)
+    }
+}
 
-                        // Create event_api::Event to send to dispatcher
-                        let decision_event =
-                            event_api::Event::decision(account_id, user_id, campaign_id, experiment_id, variation_id);
+/// Reasons collected while making a decision
+///
+/// Messages are only formatted when reasons were requested, except for critical errors which are always kept.
+struct Reasons {
+    include_reasons: bool,
+    messages: Vec<String>,
+}
 
-                        // Ignore result of the send_decision function
-                        self.client.event_dispatcher().send_event(decision_event);
-                    }
-                }
+impl Reasons {
+    fn new(include_reasons: bool) -> Reasons {
+        Reasons {
+            include_reasons,
+            messages: Vec::new(),
+        }
+    }
 
-                // Find the variation belonging to this variation ID
-                experiment.variation(variation_id)
-            }
-            None => None,
+    fn add<F: FnOnce() -> String>(&mut self, message: F) {
+        if self.include_reasons {
+            self.messages.push(message());
         }
     }
 
-    pub fn is_in_audience_of<'a>(&'a self, experiment: &'a Experiment) -> bool {
-        experiment.evaluate_audience_conditions(&|audience_id| {
-            if let Some(audience) = self.client.datafile().audience(&audience_id) {
-                return audience
-                    .conditions()
-                    .evaluate(&|condition| condition.evaluate(&self.attributes));
-            }
-            return false;
-        })
+    fn add_critical<F: FnOnce() -> String>(&mut self, message: F) {
+        self.messages.push(message());
+    }
+
+    fn into_vec(self) -> Vec<String> {
+        self.messages
     }
 }
 
@@ -1,36 +1,59 @@
 //! Parsing the Optimizely datafile
 
 use std::collections::HashMap;
+use std::sync::Arc;
 // External imports
-use error_stack::{IntoReport, Result, ResultExt};
+use error_stack::{IntoReport, Report, Result, ResultExt};
+use serde::{Deserialize, Serialize, Serializer};
 
 // Relative imports of sub modules
+use attribute::Attribute;
 use audience::Audience;
 pub use audience_condition::{
     AudienceCondition, CustomAttributeCondition, ExactCondition, ExistsCondition, NumericCondition, SubstringCondition,
 };
 pub use boolean_condition::BooleanCondition;
+pub use builder::{DatafileBuilder, FlagBuilder, RuleBuilder, VariationBuilder};
+pub use diff::{Changes, DatafileDiff};
 use environment::Environment;
 pub use error::DatafileError;
 use event::Event;
 pub(crate) use experiment::Experiment;
-pub(crate) use feature_flag::FeatureFlag;
+pub(crate) use feature_flag::{FeatureFlag, Rule};
+pub use optimizely_config::{
+    OptimizelyAttribute, OptimizelyAudience, OptimizelyConfig, OptimizelyEvent, OptimizelyExperiment,
+    OptimizelyFeatureFlag, OptimizelyVariation,
+};
 use rollout::Rollout;
 use traffic_allocation::TrafficAllocation;
+pub use validation::InvalidReference;
+pub(crate) use variable::Variable;
 pub(crate) use variation::Variation;
 
+mod attribute;
 mod audience;
 mod audience_condition;
 mod boolean_condition;
+mod builder;
+mod diff;
 mod environment;
 mod error;
 mod event;
 mod experiment;
 mod feature_flag;
+pub mod lint;
+mod optimizely_config;
 mod rollout;
 mod traffic_allocation;
+mod validation;
+mod variable;
 pub mod variation;
 
+// Versions of the datafile format that can be parsed
+//
+// Version 2 has no feature flags and rollouts, and versions before 4 have no typed audiences.
+const SUPPORTED_VERSIONS: [&str; 3] = ["2", "3", "4"];
+
 /// The datafile contains all the feature flags, experiments, events and other configuration from an Optimizely account.
 ///
 /// This configuration is stored in JSON format.
@@ -41,16 +64,57 @@ pub mod variation;
 /// containing the `content`.
 /// This would mean that a lot of memory would stay allocated for JSON syntax and unused properties.
 /// Instead the relevant fields are copied into their own `String`s.
-#[derive(Debug)]
+///
+/// A `Datafile` can be serialized into a datafile of the latest version, which can be built again.
+///
+/// ```
+/// use optimizely::datafile::Datafile;
+/// #
+/// # let file_path = "../datafiles/sandbox.json";
+///
+/// let content = std::fs::read_to_string(file_path)?;
+/// let datafile = Datafile::build(&content)?;
+///
+/// // Write the datafile as JSON, for example to store it as a fixture
+/// let json = serde_json::to_string_pretty(&datafile)?;
+/// let rebuilt = Datafile::build(&json)?;
+/// assert_eq!(rebuilt.revision(), datafile.revision());
+///
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Serialize)]
 pub struct Datafile(Environment);
 
 impl Datafile {
     /// Construct a new Datafile from a string containing a JSON document
     pub fn build(content: &str) -> Result<Datafile, DatafileError> {
+        // Check the version first, since other versions have a different structure
+        #[derive(Deserialize)]
+        struct Version {
+            version: String,
+        }
+        let version = parse::<Version>(content)?.version;
+        if !SUPPORTED_VERSIONS.contains(&version.as_str()) {
+            return Err(Report::new(DatafileError::UnsupportedVersion(version)));
+        }
+
         // Parse the JSON content via Serde into Rust structs
-        let environment: Environment = serde_json::from_str(content)
-            .into_report()
-            .change_context(DatafileError::InvalidJson)?;
+        let mut environment: Environment = parse(content)?;
+        environment.merge_legacy_audiences();
+
+        // Report every reference to an entity that does not exist, instead of only the first one
+        let invalid_references = validation::invalid_references(&environment);
+        if !invalid_references.is_empty() {
+            let mut report = Report::new(DatafileError::InvalidReferences(invalid_references.clone()));
+            for invalid_reference in invalid_references {
+                report = report.attach_printable(invalid_reference.to_string());
+            }
+            return Err(report);
+        }
+
+        // Resolve references between entities once, instead of on every decision
+        environment.resolve_experiments();
+        environment.resolve_flag_rules();
 
         Ok(Datafile(environment))
     }
@@ -65,19 +129,24 @@ impl Datafile {
         self.0.revision()
     }
 
+    /// Whether decision events should also be sent for rollouts and flags that are off
+    pub fn send_flag_decisions(&self) -> bool {
+        self.0.send_flag_decisions()
+    }
+
     /// Get the flag with the given key
     pub fn flag(&self, flag_key: &str) -> Option<&FeatureFlag> {
         self.0.feature_flags().get(flag_key)
     }
 
     /// Get all experiments
-    pub fn experiments(&self) -> &HashMap<String, Experiment> {
+    pub fn experiments(&self) -> &HashMap<String, Arc<Experiment>> {
         self.0.experiments()
     }
 
     /// Get the experiment with the given experiment ID
     pub fn experiment(&self, experiment_id: &str) -> Option<&Experiment> {
-        self.0.experiments().get(experiment_id)
+        self.0.experiments().get(experiment_id).map(Arc::as_ref)
     }
 
     /// Get the rollout with the given rollout ID
@@ -90,7 +159,63 @@ impl Datafile {
         self.0.events().get(event_key)
     }
 
+    /// Get the audience with the given audience ID
     pub fn audience(&self, audience_id: &str) -> Option<&Audience> {
-        self.0.audiences().get(audience_id)
+        self.0.audiences().get(audience_id).map(Arc::as_ref)
     }
+
+    /// Compare with another datafile, where `self` is the old and `other` the new datafile
+    ///
+    /// ```
+    /// use optimizely::datafile::{DatafileBuilder, FlagBuilder, RuleBuilder};
+    ///
+    /// let old = DatafileBuilder::new()
+    ///     .flag(FlagBuilder::new("checkout").rollout_rule(RuleBuilder::new("everyone_else").variation("off", 10_000)))
+    ///     .build()?;
+    /// let new = DatafileBuilder::new()
+    ///     .flag(FlagBuilder::new("checkout").rollout_rule(RuleBuilder::new("everyone_else").variation("on", 10_000)))
+    ///     .build()?;
+    ///
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.rollout_rules.modified, ["checkout.everyone_else"]);
+    /// assert!(diff.flags.is_empty());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn diff(&self, other: &Datafile) -> DatafileDiff {
+        DatafileDiff::new(self, other)
+    }
+
+    /// Create a read-only snapshot of the configuration within this datafile
+    pub fn optimizely_config(&self) -> OptimizelyConfig {
+        OptimizelyConfig::new(self)
+    }
+}
+
+// Deserialize the JSON content, and keep track of the path to the field that failed
+fn parse<'de, T: Deserialize<'de>>(content: &'de str) -> Result<T, DatafileError> {
+    let mut deserializer = serde_json::Deserializer::from_str(content);
+
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|error| {
+        let context = DatafileError::from_json_error::<T>(content, &error.path().to_string(), error.inner());
+        Report::new(error.into_inner()).change_context(context)
+    })?;
+
+    // Only whitespace is allowed after the JSON document
+    deserializer
+        .end()
+        .into_report()
+        .change_context(DatafileError::InvalidJson)?;
+
+    Ok(value)
+}
+
+// Serialize the values of a map as an array, sorted by key so the output is stable
+fn serialize_sorted_values<S, V>(map: &HashMap<String, V>, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    V: Serialize,
+{
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(key, _)| *key);
+    serializer.collect_seq(entries.into_iter().map(|(_, value)| value))
 }
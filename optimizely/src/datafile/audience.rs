@@ -1,12 +1,18 @@
 // External imports
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 // Imports from super
 use super::{AudienceCondition, BooleanCondition};
 
-#[derive(Deserialize, Debug)]
+// Audience that only exists for backwards compatibility of the legacy `audiences` array
+pub(super) const DUMMY_AUDIENCE_ID: &str = "$opt_dummy_audience";
+
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Audience {
+    #[serde(deserialize_with = "deserialize_conditions")]
     conditions: BooleanCondition<AudienceCondition>,
     id: String,
     name: String,
@@ -14,13 +20,15 @@ pub struct Audience {
 
 impl Audience {
     // Method to deserialize an array of Audiences into a Hashmap of Audiences
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<String, Audience>, D::Error>
+    //
+    // The audiences are reference counted, so experiments can point directly at their audiences
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<String, Arc<Audience>>, D::Error>
     where
         D: Deserializer<'de>,
     {
         let mut map = HashMap::new();
         for audience in Vec::<Audience>::deserialize(deserializer)? {
-            map.insert(audience.id.clone(), audience);
+            map.insert(audience.id.clone(), Arc::new(audience));
         }
         Ok(map)
     }
@@ -43,3 +51,33 @@ impl Audience {
         &self.conditions
     }
 }
+
+// Typed audiences contain the conditions as JSON, legacy audiences contain them as a string with a JSON document
+fn deserialize_conditions<'de, D>(deserializer: D) -> Result<BooleanCondition<AudienceCondition>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let conditions = match Value::deserialize(deserializer)? {
+        Value::String(content) => {
+            let mut conditions = serde_json::from_str(&content).map_err(serde::de::Error::custom)?;
+            add_legacy_match_type(&mut conditions);
+            conditions
+        }
+        conditions => conditions,
+    };
+
+    BooleanCondition::deserialize(conditions).map_err(serde::de::Error::custom)
+}
+
+// Legacy conditions without a match type compare the attribute for equality
+fn add_legacy_match_type(conditions: &mut Value) {
+    match conditions {
+        Value::Array(conditions) => conditions.iter_mut().for_each(add_legacy_match_type),
+        Value::Object(condition) => {
+            condition
+                .entry("match")
+                .or_insert_with(|| Value::from("exact"));
+        }
+        _ => {}
+    }
+}
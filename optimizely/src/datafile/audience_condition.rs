@@ -2,15 +2,17 @@ use std::cmp::Ordering;
 
 // External imports
 use num_ord::NumOrd;
-use serde::Deserialize;
-use serde_json::value::{Number, Value};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::value::{Map, Number, Value};
 
 // Imports from crate
 use crate::client::UserAttributes;
 
-#[derive(Debug, Deserialize, PartialEq)]
+/// A single leaf condition within the conditions of an audience
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(tag = "type")]
 pub enum AudienceCondition {
+    /// Condition on one of the user attributes
     #[serde(rename = "custom_attribute")]
     CustomAttribute(CustomAttributeCondition),
     // #[serde(rename = "third_party_dimension")]
@@ -18,30 +20,45 @@ pub enum AudienceCondition {
 }
 
 impl AudienceCondition {
-    // Method to evaluate a condition
+    /// Method to evaluate a condition
     pub fn evaluate(&self, user_attributes: &UserAttributes) -> bool {
         match self {
             AudienceCondition::CustomAttribute(condition) => condition.evaluate(user_attributes),
             // AudienceCondition::ThirdPartyDimension(condition) => condition.evaluate(user_attributes),
         }
     }
+
+    /// Name of the user attribute the condition is on, if the match type is supported
+    pub fn attribute_name(&self) -> Option<&str> {
+        match self {
+            AudienceCondition::CustomAttribute(condition) => condition.attribute_name(),
+        }
+    }
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+/// Condition on a user attribute, tagged by its match type
+#[derive(Debug, Serialize, PartialEq)]
 #[serde(tag = "match")]
 pub enum CustomAttributeCondition {
+    /// Attribute is equal to the value
     #[serde(rename = "exact")]
     Exact(ExactCondition),
+    /// Attribute is present and not null
     #[serde(rename = "exists")]
     Exists(ExistsCondition),
+    /// Attribute is greater than the value
     #[serde(rename = "gt")]
     GreaterThan(NumericCondition),
+    /// Attribute is greater than or equal to the value
     #[serde(rename = "ge")]
     GreaterThanOrEqualTo(NumericCondition),
+    /// Attribute is less than the value
     #[serde(rename = "lt")]
     LessThan(NumericCondition),
+    /// Attribute is less than or equal to the value
     #[serde(rename = "le")]
     LessThanOrEqualTo(NumericCondition),
+    /// Attribute contains the value
     #[serde(rename = "substring")]
     Substring(SubstringCondition),
     // #[serde(rename = "semver_eq")]
@@ -54,12 +71,61 @@ pub enum CustomAttributeCondition {
     // SemverLessThan(SemverCondition),
     // #[serde(rename = "semver_le")]
     // SemverLessThanOrEqualTo(SemverCondition),
-    #[serde(other)]
-    Unknown,
+    /// Match type that is not supported by this SDK, with the condition as it was in the datafile
+    #[serde(untagged)]
+    Unknown(Map<String, Value>),
+}
+
+impl<'de> Deserialize<'de> for CustomAttributeCondition {
+    // Method to deserialize a condition by its match type, keeping conditions with an unsupported match type as they are
+    fn deserialize<D>(deserializer: D) -> Result<CustomAttributeCondition, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut condition = Map::<String, Value>::deserialize(deserializer)?;
+
+        let match_type = match condition.get("match") {
+            Some(Value::String(match_type)) => match_type.clone(),
+            Some(_) => return Err(de::Error::custom("match type should be a string")),
+            None => return Err(de::Error::missing_field("match")),
+        };
+
+        // A supported match type still needs all of its fields
+        let value = Value::Object(condition.clone());
+        let result = match match_type.as_str() {
+            "exact" => ExactCondition::deserialize(value).map(CustomAttributeCondition::Exact),
+            "exists" => ExistsCondition::deserialize(value).map(CustomAttributeCondition::Exists),
+            "gt" => NumericCondition::deserialize(value).map(CustomAttributeCondition::GreaterThan),
+            "ge" => NumericCondition::deserialize(value).map(CustomAttributeCondition::GreaterThanOrEqualTo),
+            "lt" => NumericCondition::deserialize(value).map(CustomAttributeCondition::LessThan),
+            "le" => NumericCondition::deserialize(value).map(CustomAttributeCondition::LessThanOrEqualTo),
+            "substring" => SubstringCondition::deserialize(value).map(CustomAttributeCondition::Substring),
+            _ => {
+                // The type is written by `AudienceCondition` again
+                condition.remove("type");
+                return Ok(CustomAttributeCondition::Unknown(condition));
+            }
+        };
+        result.map_err(de::Error::custom)
+    }
 }
 
 impl CustomAttributeCondition {
-    // Method to evaluate a condition
+    /// Name of the user attribute, unless the match type is unknown
+    pub fn attribute_name(&self) -> Option<&str> {
+        match self {
+            CustomAttributeCondition::Exact(condition) => Some(&condition.name),
+            CustomAttributeCondition::Exists(condition) => Some(&condition.name),
+            CustomAttributeCondition::GreaterThan(condition)
+            | CustomAttributeCondition::GreaterThanOrEqualTo(condition)
+            | CustomAttributeCondition::LessThan(condition)
+            | CustomAttributeCondition::LessThanOrEqualTo(condition) => Some(&condition.name),
+            CustomAttributeCondition::Substring(condition) => Some(&condition.name),
+            CustomAttributeCondition::Unknown(_) => None,
+        }
+    }
+
+    /// Method to evaluate a condition
     pub fn evaluate(&self, user_attributes: &UserAttributes) -> bool {
         match self {
             CustomAttributeCondition::Exact(condition) => condition.evaluate(user_attributes),
@@ -77,7 +143,7 @@ impl CustomAttributeCondition {
                 .compare(user_attributes)
                 .is_some_and(|x| x.is_le()),
             CustomAttributeCondition::Substring(condition) => condition.evaluate(user_attributes),
-            CustomAttributeCondition::Unknown => {
+            CustomAttributeCondition::Unknown(_) => {
                 log::warn!("unrecognized match type in audience condition");
                 false
             }
@@ -85,14 +151,17 @@ impl CustomAttributeCondition {
     }
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+/// Condition for the `exact` match type
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct ExactCondition {
+    /// Name of the user attribute
     pub name: String,
+    /// Value the user attribute should be equal to
     pub value: Value,
 }
 
 impl ExactCondition {
-    // Method to evaluate a condition
+    /// Method to evaluate a condition
     pub fn evaluate(&self, user_attributes: &UserAttributes) -> bool {
         let optional_user_value = user_attributes.get(&self.name);
         if optional_user_value.is_none() {
@@ -103,27 +172,26 @@ impl ExactCondition {
             return false;
         }
         match &self.value {
-            Value::Bool(condition_value) => {
-                return user_value.as_bool().is_some_and(|x| x == condition_value);
-            }
-            Value::Number(condition_value) => {
-                return user_value.as_number().is_some_and(|x| x == condition_value);
-            }
-            Value::String(condition_value) => {
-                return user_value.as_str().is_some_and(|x| x == condition_value);
-            }
+            Value::Bool(condition_value) => user_value.as_bool().is_some_and(|x| x == condition_value),
+            Value::Number(condition_value) => user_value.as_number().is_some_and(|x| x == condition_value),
+            Value::String(condition_value) => match user_value.as_list() {
+                // A list matches when one of its elements is equal
+                Some(list) => list.iter().any(|x| x == condition_value),
+                None => user_value.as_str().is_some_and(|x| x == condition_value),
+            },
             _ => false,
         }
     }
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+/// Condition for the `exists` match type
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct ExistsCondition {
     name: String,
 }
 
 impl ExistsCondition {
-    // Method to evaluate a condition
+    /// Method to evaluate a condition
     pub fn evaluate(&self, user_attributes: &UserAttributes) -> bool {
         user_attributes
             .get(&self.name)
@@ -131,13 +199,16 @@ impl ExistsCondition {
     }
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+/// Condition for the numeric match types `gt`, `ge`, `lt` and `le`
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct NumericCondition {
     name: String,
     value: Number,
 }
 
 impl NumericCondition {
+    /// Compare the user attribute against the value of the condition
+    //
     // Returns Some<Equal> if user_attributes.get(self.name) == self.value
     // Returns Some<Greater> if user_attributes.get(self.name) > self.value
     // Returns Some<Less> if user_attributes.get(self.name) < self.value
@@ -176,19 +247,26 @@ impl NumericCondition {
     }
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+/// Condition for the `substring` match type
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct SubstringCondition {
+    /// Name of the user attribute
     pub name: String,
+    /// Value the user attribute should contain
     pub value: String,
 }
 
 impl SubstringCondition {
-    // Method to evaluate a condition
+    /// Method to evaluate a condition
     pub fn evaluate(&self, user_attributes: &UserAttributes) -> bool {
-        return user_attributes.get(&self.name).is_some_and(|attribute| {
-            attribute
-                .as_str()
-                .is_some_and(|str_attribute| str_attribute.contains(&self.value))
-        });
+        user_attributes
+            .get(&self.name)
+            .is_some_and(|attribute| match attribute.as_list() {
+                // A list matches when one of its elements contains the value
+                Some(list) => list.iter().any(|element| element.contains(&self.value)),
+                None => attribute
+                    .as_str()
+                    .is_some_and(|str_attribute| str_attribute.contains(&self.value)),
+            })
     }
 }
@@ -3,16 +3,22 @@ use serde::de::value::{
     StrDeserializer, U128Deserializer, U64Deserializer,
 };
 // External imports
-use serde::{de::Visitor, Deserialize, Deserializer};
+use serde::ser::SerializeSeq;
+use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
 use serde_value::{Value, ValueDeserializer};
 use std::cmp;
 use std::marker::PhantomData;
 
+/// Tree of conditions combined with the boolean operators `and`, `or` and `not`
 #[derive(Debug, PartialEq)]
 pub enum BooleanCondition<T> {
+    /// All of the conditions should be true
     And(Vec<Box<BooleanCondition<T>>>),
+    /// At least one of the conditions should be true
     Or(Vec<Box<BooleanCondition<T>>>),
+    /// The condition should be false
     Not(Option<Box<BooleanCondition<T>>>),
+    /// A leaf condition
     Single(T),
 }
 
@@ -164,8 +170,33 @@ where
     }
 }
 
+impl<T> Serialize for BooleanCondition<T>
+where
+    T: Serialize,
+{
+    // Method to serialize a BooleanCondition into the same array format it is deserialized from
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (operator, conditions) = match self {
+            BooleanCondition::And(conditions) => ("and", conditions.iter().collect::<Vec<_>>()),
+            BooleanCondition::Or(conditions) => ("or", conditions.iter().collect()),
+            BooleanCondition::Not(condition) => ("not", condition.iter().collect()),
+            BooleanCondition::Single(condition) => return condition.serialize(serializer),
+        };
+
+        let mut seq = serializer.serialize_seq(Some(conditions.len() + 1))?;
+        seq.serialize_element(operator)?;
+        for condition in conditions {
+            seq.serialize_element(condition)?;
+        }
+        seq.end()
+    }
+}
+
 impl<T> BooleanCondition<T> {
-    // Method to evaluate a condition
+    /// Method to evaluate a condition
     pub fn evaluate<E>(&self, evaluator: &E) -> bool
     where
         E: Fn(&T) -> bool,
@@ -177,16 +208,38 @@ impl<T> BooleanCondition<T> {
             BooleanCondition::Or(conditions) => conditions
                 .iter()
                 .any(|condition| condition.evaluate(evaluator)),
-            BooleanCondition::Not(option) => {
-                if let Some(condition) = option {
-                    return !condition.evaluate(evaluator);
-                }
-                return false;
-            }
-            BooleanCondition::Single(condition) => evaluator(&condition),
+            BooleanCondition::Not(option) => match option {
+                Some(condition) => !condition.evaluate(evaluator),
+                None => false,
+            },
+            BooleanCondition::Single(condition) => evaluator(condition),
+        }
+    }
+
+    /// Same tree of conditions, with every leaf condition converted by `f`
+    pub fn map<U, F>(&self, f: &F) -> BooleanCondition<U>
+    where
+        F: Fn(&T) -> U,
+    {
+        let map_all = |conditions: &Vec<Box<BooleanCondition<T>>>| {
+            conditions
+                .iter()
+                .map(|condition| Box::new(condition.map(f)))
+                .collect()
+        };
+        match self {
+            BooleanCondition::And(conditions) => BooleanCondition::And(map_all(conditions)),
+            BooleanCondition::Or(conditions) => BooleanCondition::Or(map_all(conditions)),
+            BooleanCondition::Not(condition) => BooleanCondition::Not(
+                condition
+                    .as_ref()
+                    .map(|condition| Box::new(condition.map(f))),
+            ),
+            BooleanCondition::Single(condition) => BooleanCondition::Single(f(condition)),
         }
     }
 
+    /// Whether there are no conditions to evaluate
     pub fn is_empty(&self) -> bool {
         match self {
             BooleanCondition::And(conditions) => conditions.is_empty(),
@@ -0,0 +1,434 @@
+// External imports
+use error_stack::Result;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+// Imports from super
+use super::{Datafile, DatafileError};
+
+// Generated ids start here, so they do not look like small numbers used elsewhere in tests
+const FIRST_ID: u64 = 10_000;
+
+/// Builder to declare a datafile in Rust code instead of JSON
+///
+/// Ids are generated, and audiences and experiments are referred to by their name or key.
+/// Traffic is given in basis points, so a variation with a traffic of `5_000` gets half of the users.
+///
+/// ```
+/// use optimizely::datafile::{DatafileBuilder, FlagBuilder, RuleBuilder, VariationBuilder};
+/// use serde_json::json;
+///
+/// let datafile = DatafileBuilder::new()
+///     .revision(42)
+///     .attribute("platform")
+///     .audience("web", json!(["or", {"type": "custom_attribute", "match": "exact", "name": "platform", "value": "web"}]))
+///     .event("purchase", ["checkout_experiment"])
+///     .flag(
+///         FlagBuilder::new("checkout")
+///             .variable("button_color", "string", "blue")
+///             .experiment(
+///                 RuleBuilder::new("checkout_experiment")
+///                     .audience("web")
+///                     .variation("control", 5_000)
+///                     .with_variation(VariationBuilder::new("treatment", 5_000).variable("button_color", "green")),
+///             )
+///             .rollout_rule(RuleBuilder::new("everyone_else").variation("on", 10_000)),
+///     )
+///     .build()?;
+///
+/// assert_eq!(datafile.revision(), 42);
+/// assert!(datafile.flag("checkout").is_some());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct DatafileBuilder {
+    account_id: String,
+    project_id: String,
+    environment_key: String,
+    revision: u32,
+    send_flag_decisions: bool,
+    attributes: Vec<String>,
+    audiences: Vec<(String, Value)>,
+    events: Vec<(String, Vec<String>)>,
+    flags: Vec<FlagBuilder>,
+}
+
+impl Default for DatafileBuilder {
+    fn default() -> DatafileBuilder {
+        DatafileBuilder {
+            account_id: String::from("0"),
+            project_id: String::from("0"),
+            environment_key: String::from("development"),
+            revision: 1,
+            send_flag_decisions: true,
+            attributes: Vec::new(),
+            audiences: Vec::new(),
+            events: Vec::new(),
+            flags: Vec::new(),
+        }
+    }
+}
+
+impl DatafileBuilder {
+    /// Constructor for an empty datafile
+    pub fn new() -> DatafileBuilder {
+        DatafileBuilder::default()
+    }
+
+    /// Set the account ID, which is used in events
+    pub fn account_id<T: Into<String>>(mut self, account_id: T) -> DatafileBuilder {
+        self.account_id = account_id.into();
+        self
+    }
+
+    /// Set the project ID
+    pub fn project_id<T: Into<String>>(mut self, project_id: T) -> DatafileBuilder {
+        self.project_id = project_id.into();
+        self
+    }
+
+    /// Set the environment key
+    pub fn environment_key<T: Into<String>>(mut self, environment_key: T) -> DatafileBuilder {
+        self.environment_key = environment_key.into();
+        self
+    }
+
+    /// Set the revision
+    pub fn revision(mut self, revision: u32) -> DatafileBuilder {
+        self.revision = revision;
+        self
+    }
+
+    /// Set whether decision events are sent for rollout rules as well
+    pub fn send_flag_decisions(mut self, send_flag_decisions: bool) -> DatafileBuilder {
+        self.send_flag_decisions = send_flag_decisions;
+        self
+    }
+
+    /// Declare a user attribute
+    pub fn attribute<T: Into<String>>(mut self, key: T) -> DatafileBuilder {
+        self.attributes.push(key.into());
+        self
+    }
+
+    /// Declare an audience with its conditions in the JSON format of typed audiences
+    pub fn audience<T: Into<String>>(mut self, name: T, conditions: Value) -> DatafileBuilder {
+        self.audiences.push((name.into(), conditions));
+        self
+    }
+
+    /// Declare an event, which is tied to the experiments with the given keys
+    pub fn event<T, I>(mut self, key: T, experiment_keys: I) -> DatafileBuilder
+    where
+        T: Into<String>,
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let experiment_keys = experiment_keys.into_iter().map(Into::into).collect();
+        self.events.push((key.into(), experiment_keys));
+        self
+    }
+
+    /// Declare a feature flag
+    pub fn flag(mut self, flag: FlagBuilder) -> DatafileBuilder {
+        self.flags.push(flag);
+        self
+    }
+
+    /// Generate the JSON document of the datafile
+    pub fn to_json(&self) -> Value {
+        let mut ids = IdGenerator::default();
+
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|key| json!({ "id": ids.generate(), "key": key }))
+            .collect::<Vec<_>>();
+
+        // Audiences are referred to by name, unknown names are kept so `lint::check` reports them
+        let mut audience_ids = HashMap::new();
+        let audiences = self
+            .audiences
+            .iter()
+            .map(|(name, conditions)| {
+                let id = ids.generate();
+                audience_ids.insert(name.as_str(), id.clone());
+                json!({ "id": id, "name": name, "conditions": conditions })
+            })
+            .collect::<Vec<_>>();
+        let audience_id = |name: &String| audience_ids.get(name.as_str()).unwrap_or(name).clone();
+
+        let mut experiments = Vec::new();
+        let mut rollouts = Vec::new();
+        let mut feature_flags = Vec::new();
+        let mut experiment_ids = HashMap::new();
+        for flag in &self.flags {
+            let variable_ids = flag
+                .variables
+                .iter()
+                .map(|(key, _, _)| (key.as_str(), ids.generate()))
+                .collect::<HashMap<_, _>>();
+
+            let mut flag_experiment_ids = Vec::new();
+            for rule in &flag.experiments {
+                let experiment = rule.to_json(&mut ids, &audience_id, &variable_ids);
+                experiment_ids.insert(rule.key.as_str(), experiment["id"].clone());
+                flag_experiment_ids.push(experiment["id"].clone());
+                experiments.push(experiment);
+            }
+
+            let rollout_id = ids.generate();
+            let rollout_experiments = flag
+                .rollout_rules
+                .iter()
+                .map(|rule| rule.to_json(&mut ids, &audience_id, &variable_ids))
+                .collect::<Vec<_>>();
+            rollouts.push(json!({ "id": rollout_id, "experiments": rollout_experiments }));
+
+            let variables = flag
+                .variables
+                .iter()
+                .map(|(key, variable_type, default_value)| {
+                    json!({
+                        "id": variable_ids[key.as_str()],
+                        "key": key,
+                        "type": variable_type,
+                        "defaultValue": default_value,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            feature_flags.push(json!({
+                "id": ids.generate(),
+                "key": flag.key,
+                "rolloutId": rollout_id,
+                "experimentIds": flag_experiment_ids,
+                "variables": variables,
+            }));
+        }
+
+        let events = self
+            .events
+            .iter()
+            .map(|(key, experiment_keys)| {
+                let event_experiment_ids = experiment_keys
+                    .iter()
+                    .map(|key| {
+                        experiment_ids
+                            .get(key.as_str())
+                            .cloned()
+                            .unwrap_or_else(|| json!(key))
+                    })
+                    .collect::<Vec<_>>();
+                json!({ "id": ids.generate(), "key": key, "experimentIds": event_experiment_ids })
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "version": "4",
+            "accountId": self.account_id,
+            "projectId": self.project_id,
+            "environmentKey": self.environment_key,
+            "revision": self.revision.to_string(),
+            "botFiltering": false,
+            "anonymizeIP": true,
+            "sendFlagDecisions": self.send_flag_decisions,
+            "attributes": attributes,
+            "typedAudiences": audiences,
+            "audiences": [],
+            "events": events,
+            "experiments": experiments,
+            "rollouts": rollouts,
+            "featureFlags": feature_flags,
+            "groups": [],
+        })
+    }
+
+    /// Build the datafile, which is validated like any other datafile
+    ///
+    /// Unknown audience names and event experiment keys do not fail the build, use `lint::check` to find them.
+    pub fn build(&self) -> Result<Datafile, DatafileError> {
+        Datafile::build(&self.to_json().to_string())
+    }
+}
+
+/// Builder for a feature flag of a `DatafileBuilder`
+#[derive(Debug, Clone)]
+pub struct FlagBuilder {
+    key: String,
+    variables: Vec<(String, String, String)>,
+    experiments: Vec<RuleBuilder>,
+    rollout_rules: Vec<RuleBuilder>,
+}
+
+impl FlagBuilder {
+    /// Constructor for a flag without variables or rules
+    pub fn new<T: Into<String>>(key: T) -> FlagBuilder {
+        FlagBuilder {
+            key: key.into(),
+            variables: Vec::new(),
+            experiments: Vec::new(),
+            rollout_rules: Vec::new(),
+        }
+    }
+
+    /// Declare a variable, with a type like "boolean", "integer", "double", "string" or "json"
+    pub fn variable<K, T, V>(mut self, key: K, variable_type: T, default_value: V) -> FlagBuilder
+    where
+        K: Into<String>,
+        T: Into<String>,
+        V: Into<String>,
+    {
+        self.variables
+            .push((key.into(), variable_type.into(), default_value.into()));
+        self
+    }
+
+    /// Add an experiment rule, which is evaluated before the rollout rules
+    pub fn experiment(mut self, rule: RuleBuilder) -> FlagBuilder {
+        self.experiments.push(rule);
+        self
+    }
+
+    /// Add a rule to the rollout of the flag
+    pub fn rollout_rule(mut self, rule: RuleBuilder) -> FlagBuilder {
+        self.rollout_rules.push(rule);
+        self
+    }
+}
+
+/// Builder for an experiment rule or rollout rule of a `FlagBuilder`
+#[derive(Debug, Clone)]
+pub struct RuleBuilder {
+    key: String,
+    audiences: Vec<String>,
+    variations: Vec<VariationBuilder>,
+}
+
+impl RuleBuilder {
+    /// Constructor for a rule without audiences, so every user is targeted
+    pub fn new<T: Into<String>>(key: T) -> RuleBuilder {
+        RuleBuilder {
+            key: key.into(),
+            audiences: Vec::new(),
+            variations: Vec::new(),
+        }
+    }
+
+    /// Target users in the audience with the given name, users in any of the audiences are targeted
+    pub fn audience<T: Into<String>>(mut self, name: T) -> RuleBuilder {
+        self.audiences.push(name.into());
+        self
+    }
+
+    /// Add a variation that gets the given traffic in basis points, the variation "off" disables the feature
+    pub fn variation<T: Into<String>>(self, key: T, traffic: u64) -> RuleBuilder {
+        self.with_variation(VariationBuilder::new(key, traffic))
+    }
+
+    /// Add a variation declared with its own builder
+    pub fn with_variation(mut self, variation: VariationBuilder) -> RuleBuilder {
+        self.variations.push(variation);
+        self
+    }
+
+    fn to_json<F>(&self, ids: &mut IdGenerator, audience_id: &F, variable_ids: &HashMap<&str, String>) -> Value
+    where
+        F: Fn(&String) -> String,
+    {
+        let id = ids.generate();
+        let audience_ids = self.audiences.iter().map(audience_id).collect::<Vec<_>>();
+
+        let mut end_of_range = 0;
+        let mut variations = Vec::new();
+        let mut traffic_allocation = Vec::new();
+        for variation in &self.variations {
+            let variation_id = ids.generate();
+            let variables = variation
+                .variables
+                .iter()
+                .map(|(key, value)| {
+                    // Values of undeclared variables are kept, but refer to a variable id that does not exist
+                    let variable_id = variable_ids.get(key.as_str()).unwrap_or(key);
+                    json!({ "id": variable_id, "value": value })
+                })
+                .collect::<Vec<_>>();
+            variations.push(json!({
+                "id": variation_id,
+                "key": variation.key,
+                "featureEnabled": variation.enabled,
+                "variables": variables,
+            }));
+
+            // Traffic of a variation without any share is not allocated at all
+            if variation.traffic > 0 {
+                end_of_range += variation.traffic;
+                traffic_allocation.push(json!({ "entityId": variation_id, "endOfRange": end_of_range }));
+            }
+        }
+
+        let mut audience_conditions = vec![json!("or")];
+        audience_conditions.extend(audience_ids.iter().map(|id| json!(id)));
+
+        json!({
+            "id": id,
+            "key": self.key,
+            "status": "Running",
+            "layerId": ids.generate(),
+            "audienceIds": audience_ids,
+            "audienceConditions": audience_conditions,
+            "trafficAllocation": traffic_allocation,
+            "variations": variations,
+            "forcedVariations": {},
+        })
+    }
+}
+
+/// Builder for a variation of a `RuleBuilder`
+#[derive(Debug, Clone)]
+pub struct VariationBuilder {
+    key: String,
+    traffic: u64,
+    enabled: bool,
+    variables: Vec<(String, String)>,
+}
+
+impl VariationBuilder {
+    /// Constructor for a variation with the given traffic in basis points
+    ///
+    /// The feature is enabled in every variation, except for the variation with key "off".
+    pub fn new<T: Into<String>>(key: T, traffic: u64) -> VariationBuilder {
+        let key = key.into();
+        VariationBuilder {
+            enabled: key != "off",
+            key,
+            traffic,
+            variables: Vec::new(),
+        }
+    }
+
+    /// Set whether the feature is enabled in this variation
+    pub fn enabled(mut self, enabled: bool) -> VariationBuilder {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set the value of a variable of the flag for this variation
+    pub fn variable<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> VariationBuilder {
+        self.variables.push((key.into(), value.into()));
+        self
+    }
+}
+
+// Sequential ids, formatted as strings like in datafiles from Optimizely
+#[derive(Default)]
+struct IdGenerator {
+    count: u64,
+}
+
+impl IdGenerator {
+    fn generate(&mut self) -> String {
+        self.count += 1;
+        (FIRST_ID + self.count).to_string()
+    }
+}
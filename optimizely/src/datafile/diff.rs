@@ -0,0 +1,172 @@
+// External imports
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+// Imports from super
+use super::audience::DUMMY_AUDIENCE_ID;
+use super::{Datafile, Experiment};
+
+/// Keys of the entities of one kind that were added, removed or modified, each sorted
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Changes {
+    /// Keys that only exist in the new datafile
+    pub added: Vec<String>,
+    /// Keys that only exist in the old datafile
+    pub removed: Vec<String>,
+    /// Keys that exist in both datafiles, with a different definition
+    pub modified: Vec<String>,
+}
+
+impl Changes {
+    /// Whether nothing was added, removed or modified
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Differences between two datafiles, as returned by `Datafile::diff`
+///
+/// Entities are named by human-readable keys instead of their ids:
+/// - flags and experiments by their key
+/// - rollout rules and variables by the flag key and their own key, like `buy_button.everyone_else`
+/// - audiences by their name
+/// - traffic allocations by the key of their experiment or rollout rule
+///
+/// A change to a variable or a traffic allocation also marks its flag or rule as modified.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatafileDiff {
+    /// Changes to feature flags
+    pub flags: Changes,
+    /// Changes to experiment rules
+    pub experiments: Changes,
+    /// Changes to the rules in the rollouts of flags
+    pub rollout_rules: Changes,
+    /// Changes to audiences
+    pub audiences: Changes,
+    /// Changes to variables of flags
+    pub variables: Changes,
+    /// Changes to the traffic allocations of experiments and rollout rules
+    pub traffic_allocations: Changes,
+}
+
+impl DatafileDiff {
+    pub(super) fn new(old: &Datafile, new: &Datafile) -> DatafileDiff {
+        let old = Entities::new(old);
+        let new = Entities::new(new);
+
+        DatafileDiff {
+            flags: compare(&old.flags, &new.flags),
+            experiments: compare(&old.experiments, &new.experiments),
+            rollout_rules: compare(&old.rollout_rules, &new.rollout_rules),
+            audiences: compare(&old.audiences, &new.audiences),
+            variables: compare(&old.variables, &new.variables),
+            traffic_allocations: compare(&old.traffic_allocations, &new.traffic_allocations),
+        }
+    }
+
+    /// Whether both datafiles have the same configuration
+    pub fn is_empty(&self) -> bool {
+        self.flags.is_empty()
+            && self.experiments.is_empty()
+            && self.rollout_rules.is_empty()
+            && self.audiences.is_empty()
+            && self.variables.is_empty()
+            && self.traffic_allocations.is_empty()
+    }
+}
+
+// Serialized definition of every entity, by the key that is used in the diff
+#[derive(Default)]
+struct Entities {
+    flags: BTreeMap<String, Value>,
+    experiments: BTreeMap<String, Value>,
+    rollout_rules: BTreeMap<String, Value>,
+    audiences: BTreeMap<String, Value>,
+    variables: BTreeMap<String, Value>,
+    traffic_allocations: BTreeMap<String, Value>,
+}
+
+impl Entities {
+    fn new(datafile: &Datafile) -> Entities {
+        let environment = &datafile.0;
+        let mut entities = Entities::default();
+
+        for experiment in environment.experiments().values() {
+            entities.add_rule(experiment.key().to_owned(), experiment, false);
+        }
+
+        // Rollouts have no key of their own, so their rules are named after the flag
+        for flag in environment.feature_flags().values() {
+            entities.flags.insert(flag.key().to_owned(), to_value(flag));
+
+            for variable in flag.variables() {
+                let key = format!("{}.{}", flag.key(), variable.key());
+                entities.variables.insert(key, to_value(variable));
+            }
+
+            if let Some(rollout) = environment.rollouts().get(flag.rollout_id()) {
+                for experiment in rollout.experiments() {
+                    let key = format!("{}.{}", flag.key(), experiment.key());
+                    entities.add_rule(key, experiment, true);
+                }
+            }
+        }
+
+        for audience in environment.audiences().values() {
+            if audience.id() != DUMMY_AUDIENCE_ID {
+                entities
+                    .audiences
+                    .insert(audience.name().to_owned(), to_value(audience));
+            }
+        }
+
+        entities
+    }
+
+    fn add_rule(&mut self, key: String, experiment: &Experiment, is_rollout_rule: bool) {
+        // Ranges refer to variations by their key, so the allocation reads the same as in the app
+        let traffic_allocation = experiment
+            .traffic_allocation()
+            .ranges()
+            .map(|(end, variation_id)| {
+                let variation_key = experiment
+                    .variation(variation_id)
+                    .map_or(variation_id, |variation| variation.key());
+                json!({ "variation": variation_key, "endOfRange": end })
+            })
+            .collect();
+        self.traffic_allocations
+            .insert(key.clone(), traffic_allocation);
+
+        let rules = if is_rollout_rule {
+            &mut self.rollout_rules
+        } else {
+            &mut self.experiments
+        };
+        rules.insert(key, to_value(experiment));
+    }
+}
+
+fn to_value<T: Serialize>(entity: &T) -> Value {
+    // Entities of a datafile only contain strings, numbers and maps with string keys
+    serde_json::to_value(entity).unwrap_or(Value::Null)
+}
+
+fn compare(old: &BTreeMap<String, Value>, new: &BTreeMap<String, Value>) -> Changes {
+    let mut changes = Changes::default();
+    for (key, old_value) in old {
+        match new.get(key) {
+            None => changes.removed.push(key.clone()),
+            Some(new_value) if new_value != old_value => changes.modified.push(key.clone()),
+            Some(_) => {}
+        }
+    }
+    changes.added = new
+        .keys()
+        .filter(|key| !old.contains_key(*key))
+        .cloned()
+        .collect();
+    changes
+}
@@ -1,9 +1,16 @@
 // External imports
-use serde::{Deserialize, Deserializer};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::sync::Arc;
+
+// Imports from crate
+use crate::decision::RuleType;
 
 // Imports from super
-use super::{Audience, Event, Experiment, FeatureFlag, Rollout};
+use super::{
+    audience::DUMMY_AUDIENCE_ID, serialize_sorted_values, Attribute, Audience, Event, Experiment, FeatureFlag, Rollout,
+};
 
 #[derive(Deserialize, Debug)]
 pub struct Environment {
@@ -11,23 +18,32 @@ pub struct Environment {
     account_id: String,
     #[serde(rename = "projectId")]
     project_id: String,
-    #[serde(rename = "environmentKey")]
+    #[serde(rename = "environmentKey", default)]
     environment_key: String,
+    #[serde(rename = "sdkKey", default)]
+    sdk_key: String,
     #[serde(deserialize_with = "deserialize_revision")]
     revision: u32,
-    #[serde(rename = "botFiltering")]
+    #[serde(rename = "botFiltering", default)]
     bot_filtering: bool,
-    #[serde(rename = "anonymizeIP")]
+    #[serde(rename = "anonymizeIP", default)]
     anonymize_ip: bool,
-    #[serde(rename = "typedAudiences", deserialize_with = "Audience::deserialize")]
-    audiences: HashMap<String, Audience>,
+    #[serde(rename = "sendFlagDecisions", default)]
+    send_flag_decisions: bool,
+    #[serde(deserialize_with = "Attribute::deserialize", default)]
+    attributes: HashMap<String, Attribute>,
+    #[serde(rename = "typedAudiences", deserialize_with = "Audience::deserialize", default)]
+    audiences: HashMap<String, Arc<Audience>>,
+    // Only used to fill in audiences that are missing from `typedAudiences`
+    #[serde(rename = "audiences", deserialize_with = "Audience::deserialize", default)]
+    legacy_audiences: HashMap<String, Arc<Audience>>,
     #[serde(rename = "events", deserialize_with = "Event::deserialize")]
     events: HashMap<String, Event>,
     #[serde(deserialize_with = "Experiment::deserialize")]
-    experiments: HashMap<String, Experiment>,
-    #[serde(deserialize_with = "Rollout::deserialize")]
+    experiments: HashMap<String, Arc<Experiment>>,
+    #[serde(deserialize_with = "Rollout::deserialize", default)]
     rollouts: HashMap<String, Rollout>,
-    #[serde(rename = "featureFlags", deserialize_with = "FeatureFlag::deserialize")]
+    #[serde(rename = "featureFlags", deserialize_with = "FeatureFlag::deserialize", default)]
     feature_flags: HashMap<String, FeatureFlag>,
 }
 
@@ -51,11 +67,16 @@ impl Environment {
         &self.project_id
     }
 
-    #[allow(dead_code)]
+    /// Getter for `environment_key` field
     pub fn environment_key(&self) -> &str {
         &self.environment_key
     }
 
+    /// Getter for `sdk_key` field
+    pub fn sdk_key(&self) -> &str {
+        &self.sdk_key
+    }
+
     /// Getter for `revision` field
     pub fn revision(&self) -> u32 {
         self.revision
@@ -71,7 +92,16 @@ impl Environment {
         self.anonymize_ip
     }
 
-    pub fn audiences(&self) -> &HashMap<String, Audience> {
+    /// Getter for `send_flag_decisions` field
+    pub fn send_flag_decisions(&self) -> bool {
+        self.send_flag_decisions
+    }
+
+    pub fn attributes(&self) -> &HashMap<String, Attribute> {
+        &self.attributes
+    }
+
+    pub fn audiences(&self) -> &HashMap<String, Arc<Audience>> {
         &self.audiences
     }
 
@@ -79,10 +109,59 @@ impl Environment {
         &self.feature_flags
     }
 
-    pub fn experiments(&self) -> &HashMap<String, Experiment> {
+    pub fn experiments(&self) -> &HashMap<String, Arc<Experiment>> {
         &self.experiments
     }
 
+    /// Add the legacy audiences to the typed audiences, where typed audiences with the same id take precedence
+    ///
+    /// Older datafiles only contain legacy audiences, newer datafiles contain a legacy copy of most typed audiences.
+    pub fn merge_legacy_audiences(&mut self) {
+        for (audience_id, audience) in self.legacy_audiences.drain() {
+            self.audiences.entry(audience_id).or_insert(audience);
+        }
+    }
+
+    /// Point the experiments and rollout rules at their audiences and variations
+    ///
+    /// Should be called before `resolve_flag_rules`, while the experiments are not shared with the flags yet.
+    pub fn resolve_experiments(&mut self) {
+        let rollout_rules = self
+            .rollouts
+            .values_mut()
+            .flat_map(|rollout| rollout.experiments_mut());
+        for experiment in self.experiments.values_mut().chain(rollout_rules) {
+            Arc::get_mut(experiment)
+                .expect("experiments are only shared after resolving them")
+                .resolve(&self.audiences);
+        }
+    }
+
+    /// Point every feature flag at its experiment rules, followed by the rules of its rollout
+    ///
+    /// Done once when the datafile is built, so deciding a flag does not need to look up rules by id.
+    pub fn resolve_flag_rules(&mut self) {
+        // Datafiles that refer to rollouts that do not exist are rejected before this, but missing experiments are
+        // skipped, as the experiments of mutual exclusion groups are not parsed
+        for flag in self.feature_flags.values_mut() {
+            let experiment_rules = flag
+                .experiments_ids()
+                .iter()
+                .filter_map(|experiment_id| self.experiments.get(experiment_id))
+                .map(|experiment| (Arc::clone(experiment), RuleType::FeatureTest));
+
+            let rollout_rules = self
+                .rollouts
+                .get(flag.rollout_id())
+                .into_iter()
+                .flat_map(|rollout| rollout.experiments())
+                .map(|experiment| (Arc::clone(experiment), RuleType::Rollout));
+
+            let rules = experiment_rules.chain(rollout_rules).collect();
+            flag.set_rules(rules);
+        }
+    }
+
     pub fn rollouts(&self) -> &HashMap<String, Rollout> {
         &self.rollouts
     }
@@ -91,3 +170,52 @@ impl Environment {
         &self.events
     }
 }
+
+impl Serialize for Environment {
+    // Method to serialize the environment as a datafile of the latest version
+    //
+    // All audiences are written as typed audiences, and every list is sorted so the output is stable.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Wrapper to use a serialize function for a single field
+        struct SortedValues<'a, V>(&'a HashMap<String, V>);
+
+        impl<V: Serialize> Serialize for SortedValues<'_, V> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serialize_sorted_values(self.0, serializer)
+            }
+        }
+
+        let mut audiences = self
+            .audiences
+            .values()
+            .filter(|audience| audience.id() != DUMMY_AUDIENCE_ID)
+            .collect::<Vec<_>>();
+        audiences.sort_by_key(|audience| audience.id());
+
+        let mut state = serializer.serialize_struct("Environment", 17)?;
+        state.serialize_field("version", "4")?;
+        state.serialize_field("accountId", &self.account_id)?;
+        state.serialize_field("projectId", &self.project_id)?;
+        state.serialize_field("environmentKey", &self.environment_key)?;
+        state.serialize_field("sdkKey", &self.sdk_key)?;
+        state.serialize_field("revision", &self.revision.to_string())?;
+        state.serialize_field("botFiltering", &self.bot_filtering)?;
+        state.serialize_field("anonymizeIP", &self.anonymize_ip)?;
+        state.serialize_field("sendFlagDecisions", &self.send_flag_decisions)?;
+        state.serialize_field("attributes", &SortedValues(&self.attributes))?;
+        state.serialize_field("typedAudiences", &audiences)?;
+        state.serialize_field("audiences", &[(); 0])?;
+        state.serialize_field("events", &SortedValues(&self.events))?;
+        state.serialize_field("experiments", &SortedValues(&self.experiments))?;
+        state.serialize_field("rollouts", &SortedValues(&self.rollouts))?;
+        state.serialize_field("featureFlags", &SortedValues(&self.feature_flags))?;
+        state.serialize_field("groups", &[(); 0])?;
+        state.end()
+    }
+}
@@ -1,10 +1,108 @@
 // External imports
+use serde::{de, Deserialize};
+use serde_json::error::Category;
+use serde_value::ValueDeserializer;
+use std::fmt;
 use thiserror::Error;
 
+// Imports from super
+use super::InvalidReference;
+
 /// This type represents all possible errors that can occur when parsing the datafile
+///
+/// Variants that describe invalid content carry the JSON path of the failure,
+/// for example `experiments[3].trafficAllocation[1].endOfRange`.
 #[derive(Error, Debug, PartialEq)]
 pub enum DatafileError {
     #[doc(hidden)]
     #[error("JSON can not be parsed")]
     InvalidJson,
+    #[doc(hidden)]
+    #[error("Datafile version {0} is not supported")]
+    UnsupportedVersion(String),
+    #[doc(hidden)]
+    #[error("Required field `{0}` is missing")]
+    MissingField(String),
+    #[doc(hidden)]
+    #[error("Field `{0}` has an invalid type or value")]
+    InvalidType(String),
+    #[doc(hidden)]
+    #[error("Audience condition `{0}` is invalid")]
+    InvalidCondition(String),
+    #[doc(hidden)]
+    #[error("Datafile contains {} references to entities that do not exist", .0.len())]
+    InvalidReferences(Vec<InvalidReference>),
+}
+
+impl DatafileError {
+    // Classify a serde_json error that occurred at the given path, while deserializing the content as a `T`
+    pub(super) fn from_json_error<'de, T>(content: &'de str, path: &str, error: &serde_json::Error) -> DatafileError
+    where
+        T: Deserialize<'de>,
+    {
+        // Syntax errors and unexpected ends of input are not about the content of the datafile
+        if error.classify() != Category::Data {
+            return DatafileError::InvalidJson;
+        }
+
+        // The path is "." for errors at the root of the document
+        let path = path.trim_start_matches('.');
+
+        let in_conditions = path.split('.').any(|segment| {
+            let field = segment.split('[').next().unwrap_or(segment);
+            field == "conditions" || field == "audienceConditions"
+        });
+        if in_conditions {
+            return DatafileError::InvalidCondition(path.to_owned());
+        }
+
+        // The path of a missing field points to the object that should contain it
+        match missing_field::<T>(content, path) {
+            Some(field) if path.is_empty() => DatafileError::MissingField(field.to_owned()),
+            Some(field) => DatafileError::MissingField(format!("{path}.{field}")),
+            None => DatafileError::InvalidType(path.to_owned()),
+        }
+    }
+}
+
+// Error that keeps the name of a missing field, which serde_json only includes in its message
+#[derive(Debug)]
+struct FieldError {
+    missing_field: Option<&'static str>,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.missing_field {
+            Some(field) => write!(formatter, "missing field `{field}`"),
+            None => formatter.write_str("invalid value"),
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+impl de::Error for FieldError {
+    fn custom<M: fmt::Display>(_message: M) -> FieldError {
+        FieldError { missing_field: None }
+    }
+
+    fn missing_field(field: &'static str) -> FieldError {
+        FieldError {
+            missing_field: Some(field),
+        }
+    }
+}
+
+// Deserialize the content again from a generic value, to find out whether the error at the path is a missing field
+fn missing_field<'de, T: Deserialize<'de>>(content: &str, path: &str) -> Option<&'static str> {
+    let value = serde_json::from_str::<serde_value::Value>(content).ok()?;
+    let error = serde_path_to_error::deserialize::<_, T>(ValueDeserializer::<FieldError>::new(value)).err()?;
+
+    // Both deserializers should fail at the same place, but only trust the field name if they do
+    let error_path = error.path().to_string();
+    match error.inner().missing_field {
+        Some(field) if error_path.trim_start_matches('.') == path => Some(field),
+        _ => None,
+    }
 }
@@ -1,11 +1,13 @@
 // External imports
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Event {
     id: String,
     key: String,
+    #[serde(rename = "experimentIds", default)]
+    experiment_ids: Vec<String>,
 }
 
 impl Event {
@@ -32,4 +34,9 @@ impl Event {
     pub fn key(&self) -> &str {
         &self.key
     }
+
+    /// Getter for `experiment_ids` field
+    pub fn experiment_ids(&self) -> &[String] {
+        &self.experiment_ids
+    }
 }
@@ -1,37 +1,58 @@
 // External imports
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+
+// Imports from crate
+use crate::client::UserAttributes;
 
 // Imports from super
-use super::{BooleanCondition, TrafficAllocation, Variation};
+use super::{Audience, BooleanCondition, TrafficAllocation, Variation};
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Experiment {
     #[serde()]
     id: String,
+    // Shared with every decision for this rule, so a decision does not copy the key
     #[serde()]
-    key: String,
-    #[serde(rename = "audienceConditions")]
+    key: Arc<str>,
+    #[serde(rename = "audienceConditions", skip_serializing_if = "Option::is_none")]
     audience_conditions: Option<BooleanCondition<String>>,
     #[serde(rename = "audienceIds")]
     audience_ids: Vec<String>,
     #[serde(rename = "layerId")]
     campaign_id: String,
-    #[serde(rename = "trafficAllocation", deserialize_with = "TrafficAllocation::deserialize")]
+    #[serde(
+        rename = "trafficAllocation",
+        deserialize_with = "TrafficAllocation::deserialize",
+        serialize_with = "TrafficAllocation::serialize"
+    )]
     traffic_allocation: TrafficAllocation,
-    #[serde(rename = "variations", deserialize_with = "Variation::deserialize")]
-    variations: HashMap<String, Variation>,
+    #[serde(
+        rename = "variations",
+        deserialize_with = "Variation::deserialize",
+        serialize_with = "super::serialize_sorted_values"
+    )]
+    variations: HashMap<String, Arc<Variation>>,
+    // Audience conditions with every audience id resolved to its audience, `None` when every user is targeted
+    #[serde(skip)]
+    audiences: Option<BooleanCondition<Option<Arc<Audience>>>>,
+    // Traffic allocation as end of the range with its variation, ordered by the end of the range
+    #[serde(skip)]
+    buckets: Vec<(u64, Option<Arc<Variation>>)>,
 }
 
 impl Experiment {
     // Method to deserialize an array of Experiments into a Hashmap of Experiments
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<String, Experiment>, D::Error>
+    //
+    // The experiments are reference counted, so feature flags can point directly at their experiment rules
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<String, Arc<Experiment>>, D::Error>
     where
         D: Deserializer<'de>,
     {
         let mut map = HashMap::new();
         for experiment in Vec::<Experiment>::deserialize(deserializer)? {
-            map.insert(experiment.id.clone(), experiment);
+            map.insert(experiment.id.clone(), Arc::new(experiment));
         }
         Ok(map)
     }
@@ -57,29 +78,91 @@ impl Experiment {
     }
 
     pub fn variation(&self, variation_id: &str) -> Option<&Variation> {
-        self.variations.get(variation_id)
+        self.variations.get(variation_id).map(Arc::as_ref)
     }
 
-    pub fn evaluate_audience_conditions<E>(&self, evaluator: &E) -> bool
-    where
-        E: Fn(&String) -> bool,
-    {
-        if let Some(conditions) = &self.audience_conditions {
-            if conditions.is_empty() {
-                return true;
-            }
-            return conditions.evaluate(evaluator);
-        } else {
-            let conditions = BooleanCondition::Or(
+    pub fn variations(&self) -> &HashMap<String, Arc<Variation>> {
+        &self.variations
+    }
+
+    /// Shared reference to the key, which can be kept without copying it
+    pub(crate) fn shared_key(&self) -> &Arc<str> {
+        &self.key
+    }
+
+    pub fn audience_conditions(&self) -> Option<&BooleanCondition<String>> {
+        self.audience_conditions.as_ref()
+    }
+
+    pub fn audience_ids(&self) -> &[String] {
+        &self.audience_ids
+    }
+
+    /// Whether the rule has no audiences, so every user is targeted
+    pub fn targets_everyone(&self) -> bool {
+        match &self.audience_conditions {
+            Some(conditions) => conditions.is_empty(),
+            None => self.audience_ids.is_empty(),
+        }
+    }
+
+    /// Point the audience conditions at their audiences and the traffic allocation at its variations
+    ///
+    /// Done once when the datafile is built, so deciding a flag does not need to look up audiences or variations by id.
+    /// An audience or variation that does not exist never matches.
+    pub(super) fn resolve(&mut self, audiences: &HashMap<String, Arc<Audience>>) {
+        let resolve_audience = |audience_id: &String| audiences.get(audience_id).cloned();
+        self.audiences = match &self.audience_conditions {
+            _ if self.targets_everyone() => None,
+            Some(conditions) => Some(conditions.map(&resolve_audience)),
+            // Without audience conditions, any of the audiences should match
+            None => Some(BooleanCondition::Or(
                 self.audience_ids
                     .iter()
-                    .map(|id| Box::new(BooleanCondition::Single(id.into())))
+                    .map(|audience_id| Box::new(BooleanCondition::Single(resolve_audience(audience_id))))
                     .collect(),
-            );
-            if conditions.is_empty() {
-                return true;
-            }
-            return conditions.evaluate(evaluator);
+            )),
+        };
+
+        self.buckets = self
+            .traffic_allocation
+            .ranges()
+            .map(|(end, variation_id)| (end, self.variations.get(variation_id).cloned()))
+            .collect();
+    }
+
+    /// Whether the user attributes satisfy the audience conditions
+    pub fn evaluate_audiences(&self, user_attributes: &UserAttributes) -> bool {
+        match &self.audiences {
+            Some(conditions) => conditions.evaluate(&|audience| match audience {
+                Some(audience) => audience
+                    .conditions()
+                    .evaluate(&|condition| condition.evaluate(user_attributes)),
+                None => false,
+            }),
+            None => true,
+        }
+    }
+
+    /// Index of the bucket that contains the bucket value, if that bucket has a variation
+    pub(crate) fn bucket(&self, bucket_value: u64) -> Option<usize> {
+        // The first range that ends at or after the bucket value, found with a binary search
+        let index = self.buckets.partition_point(|(end, _)| *end < bucket_value);
+        match self.buckets.get(index) {
+            Some((_, Some(_))) => Some(index),
+            _ => None,
         }
     }
+
+    /// Variation of a bucket, in the same order as the traffic allocation
+    pub(crate) fn bucket_variation(&self, index: usize) -> Option<&Arc<Variation>> {
+        self.buckets
+            .get(index)
+            .and_then(|(_, variation)| variation.as_ref())
+    }
+
+    /// Number of buckets in the traffic allocation
+    pub(crate) fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
 }
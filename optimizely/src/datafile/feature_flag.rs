@@ -1,17 +1,60 @@
 // External imports
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
+use std::sync::Arc;
+
+// Imports from crate
+use crate::decision::RuleType;
+
+// Imports from super
+use super::{Experiment, Variable, Variation};
 
 /// Optimizely feature flag.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct FeatureFlag {
+    #[serde()]
+    id: String,
     #[serde()]
     key: String,
     #[serde(rename = "rolloutId")]
     rollout_id: String,
     #[serde(rename = "experimentIds")]
     experiment_ids: Vec<String>,
-    // TODO: variables
+    #[serde(default)]
+    variables: Vec<Variable>,
+    // Experiment rules followed by rollout rules, resolved after deserializing the whole datafile
+    #[serde(skip)]
+    rules: Vec<Rule>,
+    // Values of the variables when the user is not bucketed into any rule
+    #[serde(skip)]
+    default_variables: Arc<Map<String, Value>>,
+}
+
+/// Experiment or rollout rule of a feature flag
+#[derive(Debug)]
+pub struct Rule {
+    experiment: Arc<Experiment>,
+    rule_type: RuleType,
+    // Values of the flag variables for the variation of each bucket of the experiment
+    variables: Vec<Arc<Map<String, Value>>>,
+}
+
+impl Rule {
+    /// Getter for `experiment` field
+    pub fn experiment(&self) -> &Arc<Experiment> {
+        &self.experiment
+    }
+
+    /// Getter for `rule_type` field
+    pub fn rule_type(&self) -> RuleType {
+        self.rule_type
+    }
+
+    /// Values of the flag variables for the variation of a bucket
+    pub(crate) fn variables(&self, bucket: usize) -> &Arc<Map<String, Value>> {
+        &self.variables[bucket]
+    }
 }
 
 impl FeatureFlag {
@@ -27,6 +70,10 @@ impl FeatureFlag {
         Ok(map)
     }
 
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
     #[allow(dead_code)]
     pub fn key(&self) -> &str {
         &self.key
@@ -39,4 +86,56 @@ impl FeatureFlag {
     pub fn experiments_ids(&self) -> &Vec<String> {
         &self.experiment_ids
     }
+
+    /// Rules to evaluate in order, the first rule the user is bucketed into decides the flag
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Set the rules, and compute the values of the variables for every variation of those rules
+    pub(super) fn set_rules(&mut self, rules: Vec<(Arc<Experiment>, RuleType)>) {
+        self.rules = rules
+            .into_iter()
+            .map(|(experiment, rule_type)| {
+                let variables = (0..experiment.bucket_count())
+                    .map(|bucket| {
+                        let variation = experiment.bucket_variation(bucket);
+                        Arc::new(self.variable_values(variation.map(Arc::as_ref)))
+                    })
+                    .collect();
+                Rule {
+                    experiment,
+                    rule_type,
+                    variables,
+                }
+            })
+            .collect();
+        self.default_variables = Arc::new(self.variable_values(None));
+    }
+
+    /// Values of the variables when the user is not bucketed into any rule
+    pub(crate) fn default_variables(&self) -> &Arc<Map<String, Value>> {
+        &self.default_variables
+    }
+
+    pub fn variables(&self) -> &[Variable] {
+        &self.variables
+    }
+
+    /// Typed values of all variables for the given variation
+    ///
+    /// Variables only take the value from the variation when the feature is enabled in that variation,
+    /// otherwise (or without a variation) the default values are used.
+    pub fn variable_values(&self, variation: Option<&Variation>) -> Map<String, Value> {
+        let variation = variation.filter(|variation| variation.is_feature_enabled());
+        self.variables
+            .iter()
+            .map(|variable| {
+                let raw = variation
+                    .and_then(|variation| variation.variable_value(variable.id()))
+                    .unwrap_or_else(|| variable.default_value());
+                (variable.key().into(), variable.parse(raw))
+            })
+            .collect()
+    }
 }
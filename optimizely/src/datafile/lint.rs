@@ -0,0 +1,232 @@
+//! Checks for common mistakes in the configuration of a datafile
+//!
+//! Unlike the validation while building a `Datafile`, these checks never reject a datafile.
+//! A datafile with warnings still works, but probably does not do what was intended.
+//!
+//! ```
+//! use optimizely::datafile::lint::{self, Severity};
+//! use optimizely::datafile::{DatafileBuilder, FlagBuilder, RuleBuilder};
+//!
+//! let datafile = DatafileBuilder::new()
+//!     .flag(FlagBuilder::new("checkout").experiment(RuleBuilder::new("checkout_experiment").variation("on", 5_000)))
+//!     .build()?;
+//!
+//! for warning in lint::check(&datafile) {
+//!     if warning.severity() >= Severity::Warning {
+//!         println!("{warning}");
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+// External imports
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+// Imports from super
+use super::validation::{collect_leaves, dangling_references};
+use super::{Datafile, Environment, Experiment};
+
+// Traffic is allocated in basis points
+const MAX_TRAFFIC: u64 = 10_000;
+
+/// How likely a lint warning points at a real problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Often intentional, but worth a look
+    Info,
+    /// Probably a mistake
+    Warning,
+    /// Certainly a mistake
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The check that produced a lint warning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LintRule {
+    /// The traffic allocation of a rule does not add up to 10,000
+    TrafficAllocationSum,
+    /// An audience of a rule uses an attribute that is not in the `attributes` of the datafile
+    UndeclaredAttribute,
+    /// A variation never gets any traffic
+    VariationWithoutTraffic,
+    /// The last rule of a rollout does not target everyone
+    MissingEveryoneElseRule,
+    /// A flag refers to an experiment, a rule to an audience, or an event to an experiment, that does not exist
+    DanglingReference,
+}
+
+/// Possible mistake in the configuration of a datafile
+///
+/// The path names entities by their key, for example `experiments["buy_button_experiment"].trafficAllocation`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintWarning {
+    severity: Severity,
+    rule: LintRule,
+    path: String,
+    message: String,
+}
+
+impl LintWarning {
+    fn new(severity: Severity, rule: LintRule, path: String, message: String) -> LintWarning {
+        LintWarning {
+            severity,
+            rule,
+            path,
+            message,
+        }
+    }
+
+    /// Getter for `severity` field
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Getter for `rule` field
+    pub fn rule(&self) -> LintRule {
+        self.rule
+    }
+
+    /// Getter for `path` field
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Getter for `message` field
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.path, self.message)
+    }
+}
+
+/// Check a datafile for common mistakes, sorted by severity with the most severe first and then by path
+pub fn check(datafile: &Datafile) -> Vec<LintWarning> {
+    let environment = &datafile.0;
+    let mut warnings = Vec::new();
+
+    for experiment in environment.experiments().values() {
+        let path = format!("experiments[\"{}\"]", experiment.key());
+        check_rule(environment, &path, experiment, false, &mut warnings);
+    }
+
+    for rollout in environment.rollouts().values() {
+        let path = format!("rollouts[\"{}\"]", rollout.id());
+        for experiment in rollout.experiments() {
+            let path = format!("{path}.experiments[\"{}\"]", experiment.key());
+            check_rule(environment, &path, experiment, true, &mut warnings);
+        }
+
+        // Users that do not qualify for any rule get no variation at all
+        let has_everyone_else_rule = rollout
+            .experiments()
+            .last()
+            .is_some_and(|experiment| experiment.targets_everyone());
+        if !has_everyone_else_rule {
+            let message = String::from("the last rule of the rollout does not target everyone else");
+            warnings.push(LintWarning::new(Severity::Warning, LintRule::MissingEveryoneElseRule, path, message));
+        }
+    }
+
+    // References that would break decisions already stop the datafile from being built
+    for reference in dangling_references(environment) {
+        let message = format!("refers to {} `{}`, which does not exist", reference.entity(), reference.id());
+        let path = reference.path().to_owned();
+        warnings.push(LintWarning::new(Severity::Warning, LintRule::DanglingReference, path, message));
+    }
+
+    warnings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    warnings
+}
+
+// Check an experiment or a rule of a rollout
+fn check_rule(
+    environment: &Environment, path: &str, experiment: &Experiment, is_rollout_rule: bool,
+    warnings: &mut Vec<LintWarning>,
+) {
+    // The end of the last range is the total, since ranges are cumulative
+    let mut total = 0;
+    let mut variations_with_traffic = HashSet::new();
+    for (end, variation_id) in experiment.traffic_allocation().ranges() {
+        if end > total {
+            variations_with_traffic.insert(variation_id);
+        }
+        total = total.max(end);
+    }
+
+    // Partial traffic is the normal way to roll out a flag gradually, but rarely intended for an experiment
+    let severity = match total {
+        total if total > MAX_TRAFFIC => Some(Severity::Error),
+        total if total < MAX_TRAFFIC && is_rollout_rule => Some(Severity::Info),
+        total if total < MAX_TRAFFIC => Some(Severity::Warning),
+        _ => None,
+    };
+    if let Some(severity) = severity {
+        let message = format!("traffic allocation adds up to {total} instead of {MAX_TRAFFIC}");
+        let path = format!("{path}.trafficAllocation");
+        warnings.push(LintWarning::new(severity, LintRule::TrafficAllocationSum, path, message));
+    }
+
+    let mut variations = experiment
+        .variations()
+        .values()
+        .filter(|variation| !variations_with_traffic.contains(variation.id()))
+        .collect::<Vec<_>>();
+    variations.sort_by_key(|variation| variation.key());
+    for variation in variations {
+        let path = format!("{path}.variations[\"{}\"]", variation.key());
+        let message = String::from("variation does not get any traffic");
+        warnings.push(LintWarning::new(Severity::Info, LintRule::VariationWithoutTraffic, path, message));
+    }
+
+    // Conditions take precedence over the list of audience ids
+    let mut audience_ids = Vec::new();
+    match experiment.audience_conditions() {
+        Some(conditions) => collect_leaves(conditions, &mut audience_ids),
+        None => audience_ids.extend(experiment.audience_ids()),
+    }
+
+    // Sorted and without duplicates, since the same audience can be used more than once
+    let mut undeclared = BTreeSet::new();
+    for audience in audience_ids
+        .into_iter()
+        .filter_map(|audience_id| environment.audiences().get(audience_id))
+    {
+        let mut conditions = Vec::new();
+        collect_leaves(audience.conditions(), &mut conditions);
+        for attribute_name in conditions
+            .into_iter()
+            .filter_map(|condition| condition.attribute_name())
+        {
+            if !environment.attributes().contains_key(attribute_name) {
+                undeclared.insert((audience.name(), attribute_name));
+            }
+        }
+    }
+    for (audience_name, attribute_name) in undeclared {
+        let message = format!("audience `{audience_name}` uses attribute `{attribute_name}`, which is not declared");
+        let path = format!("{path}.audienceIds");
+        warnings.push(LintWarning::new(Severity::Warning, LintRule::UndeclaredAttribute, path, message));
+    }
+}
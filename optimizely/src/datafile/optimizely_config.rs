@@ -0,0 +1,291 @@
+// External imports
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// Imports from super
+use super::{audience::DUMMY_AUDIENCE_ID, BooleanCondition, Datafile, Experiment};
+
+/// Read-only snapshot of the configuration within a datafile
+///
+/// Unlike the `Datafile`, all of its fields are owned and public, and it can be serialized to JSON.
+///
+/// ```
+/// use optimizely::Client;
+/// #
+/// # let file_path = "../datafiles/sandbox.json";
+///
+/// // Initialize Optimizely client using local datafile
+/// let optimizely_client = Client::from_local_datafile(file_path)?
+///     .initialize();
+///
+/// // Create a snapshot of the configuration
+/// let config = optimizely_client.optimizely_config();
+/// let flag = &config.features_map["buy_button"];
+/// assert_eq!(flag.experiment_rules[0].key, "buy_button_experiment");
+///
+/// // Serialize to JSON
+/// let json = serde_json::to_string(&config)?;
+///
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizelyConfig {
+    /// Revision of the datafile
+    pub revision: u32,
+    /// SDK key of the environment, empty when not included in the datafile
+    pub sdk_key: String,
+    /// Key of the environment
+    pub environment_key: String,
+    /// Feature flags by flag key
+    pub features_map: BTreeMap<String, OptimizelyFeatureFlag>,
+    /// Experiments by experiment key, excluding rollout rules
+    pub experiments_map: BTreeMap<String, OptimizelyExperiment>,
+    /// Declared attributes
+    pub attributes: Vec<OptimizelyAttribute>,
+    /// Audiences
+    pub audiences: Vec<OptimizelyAudience>,
+    /// Conversion events
+    pub events: Vec<OptimizelyEvent>,
+}
+
+/// Feature flag within an `OptimizelyConfig`
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizelyFeatureFlag {
+    /// ID of the flag
+    pub id: String,
+    /// Key of the flag
+    pub key: String,
+    /// Experiments of the flag, in order of evaluation
+    pub experiment_rules: Vec<OptimizelyExperiment>,
+    /// Rules of the rollout of the flag, in order of evaluation
+    pub delivery_rules: Vec<OptimizelyExperiment>,
+}
+
+/// Experiment or rollout rule within an `OptimizelyConfig`
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizelyExperiment {
+    /// ID of the experiment
+    pub id: String,
+    /// Key of the experiment
+    pub key: String,
+    /// Audience conditions using the audience names, e.g. `"A" AND ("B" OR "C")`
+    pub audiences: String,
+    /// Variations by variation key
+    pub variations_map: BTreeMap<String, OptimizelyVariation>,
+}
+
+/// Variation within an `OptimizelyConfig`
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizelyVariation {
+    /// ID of the variation
+    pub id: String,
+    /// Key of the variation
+    pub key: String,
+    /// Whether the flag is enabled for this variation
+    pub feature_enabled: bool,
+}
+
+/// Audience within an `OptimizelyConfig`
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizelyAudience {
+    /// ID of the audience
+    pub id: String,
+    /// Name of the audience
+    pub name: String,
+    /// Conditions of the audience serialized as JSON
+    pub conditions: String,
+}
+
+/// Attribute within an `OptimizelyConfig`
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizelyAttribute {
+    /// ID of the attribute
+    pub id: String,
+    /// Key of the attribute
+    pub key: String,
+}
+
+/// Conversion event within an `OptimizelyConfig`
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizelyEvent {
+    /// ID of the event
+    pub id: String,
+    /// Key of the event
+    pub key: String,
+    /// IDs of the experiments that use this event as metric
+    pub experiment_ids: Vec<String>,
+}
+
+impl OptimizelyConfig {
+    pub(super) fn new(datafile: &Datafile) -> OptimizelyConfig {
+        let environment = &datafile.0;
+
+        // Project every experiment of the flags and rollouts
+        let project_experiment = |experiment: &Experiment| OptimizelyExperiment::new(datafile, experiment);
+
+        let features_map = environment
+            .feature_flags()
+            .values()
+            .map(|flag| {
+                let experiment_rules = flag
+                    .experiments_ids()
+                    .iter()
+                    .filter_map(|experiment_id| datafile.experiment(experiment_id))
+                    .map(project_experiment)
+                    .collect();
+                let delivery_rules = datafile
+                    .rollout(flag.rollout_id())
+                    .map(|rollout| {
+                        rollout
+                            .experiments()
+                            .iter()
+                            .map(|experiment| project_experiment(experiment))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let flag = OptimizelyFeatureFlag {
+                    id: flag.id().into(),
+                    key: flag.key().into(),
+                    experiment_rules,
+                    delivery_rules,
+                };
+                (flag.key.clone(), flag)
+            })
+            .collect();
+
+        let experiments_map = environment
+            .experiments()
+            .values()
+            .map(|experiment| (experiment.key().into(), project_experiment(experiment)))
+            .collect();
+
+        let mut attributes = environment
+            .attributes()
+            .values()
+            .map(|attribute| OptimizelyAttribute {
+                id: attribute.id().into(),
+                key: attribute.key().into(),
+            })
+            .collect::<Vec<_>>();
+        attributes.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let mut audiences = environment
+            .audiences()
+            .values()
+            .filter(|audience| audience.id() != DUMMY_AUDIENCE_ID)
+            .map(|audience| OptimizelyAudience {
+                id: audience.id().into(),
+                name: audience.name().into(),
+                conditions: serde_json::to_string(audience.conditions()).unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+        audiences.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut events = environment
+            .events()
+            .values()
+            .map(|event| OptimizelyEvent {
+                id: event.id().into(),
+                key: event.key().into(),
+                experiment_ids: event.experiment_ids().to_vec(),
+            })
+            .collect::<Vec<_>>();
+        events.sort_by(|a, b| a.key.cmp(&b.key));
+
+        OptimizelyConfig {
+            revision: environment.revision(),
+            sdk_key: environment.sdk_key().into(),
+            environment_key: environment.environment_key().into(),
+            features_map,
+            experiments_map,
+            attributes,
+            audiences,
+            events,
+        }
+    }
+}
+
+impl OptimizelyExperiment {
+    fn new(datafile: &Datafile, experiment: &Experiment) -> OptimizelyExperiment {
+        // Use the audience name where possible
+        let audience_name = |audience_id: &str| match datafile.audience(audience_id) {
+            Some(audience) => audience.name().to_owned(),
+            None => audience_id.to_owned(),
+        };
+
+        // Audience IDs are combined with OR when there are no explicit audience conditions
+        let audiences = match experiment.audience_conditions() {
+            Some(conditions) => serialize_audiences(conditions, &audience_name),
+            None => {
+                let conditions = experiment
+                    .audience_ids()
+                    .iter()
+                    .map(|id| Box::new(BooleanCondition::Single(id.clone())))
+                    .collect();
+                serialize_audiences(&BooleanCondition::Or(conditions), &audience_name)
+            }
+        };
+
+        let variations_map = experiment
+            .variations()
+            .values()
+            .map(|variation| {
+                let variation = OptimizelyVariation {
+                    id: variation.id().into(),
+                    key: variation.key().into(),
+                    feature_enabled: variation.is_feature_enabled(),
+                };
+                (variation.key.clone(), variation)
+            })
+            .collect();
+
+        OptimizelyExperiment {
+            id: experiment.id().into(),
+            key: experiment.key().into(),
+            audiences,
+            variations_map,
+        }
+    }
+}
+
+// Render audience conditions like `"A" AND ("B" OR NOT "C")`
+fn serialize_audiences<F>(condition: &BooleanCondition<String>, audience_name: &F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    // Nested conditions with multiple operands are wrapped in parentheses
+    let operand = |condition: &BooleanCondition<String>| {
+        let serialized = serialize_audiences(condition, audience_name);
+        match condition {
+            BooleanCondition::And(conditions) | BooleanCondition::Or(conditions) if conditions.len() > 1 => {
+                format!("({serialized})")
+            }
+            _ => serialized,
+        }
+    };
+
+    let join = |conditions: &Vec<Box<BooleanCondition<String>>>, operator: &str| {
+        conditions
+            .iter()
+            .map(|condition| operand(condition))
+            .filter(|serialized| !serialized.is_empty())
+            .collect::<Vec<_>>()
+            .join(operator)
+    };
+
+    match condition {
+        BooleanCondition::Single(audience_id) => format!("\"{}\"", audience_name(audience_id)),
+        BooleanCondition::And(conditions) => join(conditions, " AND "),
+        BooleanCondition::Or(conditions) => join(conditions, " OR "),
+        BooleanCondition::Not(Some(condition)) => format!("NOT {}", operand(condition)),
+        BooleanCondition::Not(None) => String::new(),
+    }
+}
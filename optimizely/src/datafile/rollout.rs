@@ -1,14 +1,15 @@
 // External imports
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 // Imports from super
 use super::Experiment;
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Rollout {
     id: String,
-    experiments: Vec<Experiment>,
+    experiments: Vec<Arc<Experiment>>,
 }
 
 impl Rollout {
@@ -30,7 +31,11 @@ impl Rollout {
     }
 
     #[allow(dead_code)]
-    pub fn experiments(&self) -> &Vec<Experiment> {
+    pub fn experiments(&self) -> &[Arc<Experiment>] {
         &self.experiments
     }
+
+    pub(super) fn experiments_mut(&mut self) -> &mut [Arc<Experiment>] {
+        &mut self.experiments
+    }
 }
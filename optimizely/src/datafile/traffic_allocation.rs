@@ -1,8 +1,8 @@
 // External imports
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::BTreeMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 struct Range {
     #[serde(rename = "entityId")]
     variation_id: String,
@@ -25,6 +25,29 @@ impl TrafficAllocation {
         Ok(TrafficAllocation(tree))
     }
 
+    /// Ids of all variations that get a range of the traffic
+    pub fn variation_ids(&self) -> impl Iterator<Item = &str> {
+        self.0.values().map(String::as_str)
+    }
+
+    /// Ranges of the traffic as end of the range with the variation id, ordered by the end of the range
+    pub fn ranges(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.0
+            .iter()
+            .map(|(end, variation_id)| (*end, variation_id.as_str()))
+    }
+
+    // Method to serialize the ranges back into an array, ordered by the end of the range
+    pub fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.0.iter().map(|(end, variation_id)| Range {
+            variation_id: variation_id.clone(),
+            end: *end,
+        }))
+    }
+
     #[allow(dead_code)]
     pub fn variation(&self, bucket_value: u64) -> Option<&str> {
         // Use BTreeMap::range to find the variation in O(log(n))
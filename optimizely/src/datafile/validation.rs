@@ -0,0 +1,168 @@
+// External imports
+use std::collections::HashSet;
+use std::fmt;
+
+// Imports from super
+use super::{BooleanCondition, Environment, Experiment};
+
+/// Reference from one part of the datafile to an entity that does not exist
+///
+/// References that make decisions impossible reject the datafile, the others are reported by `lint::check`.
+/// The path names entities by their key, for example `featureFlags["buy_button"].rolloutId`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct InvalidReference {
+    path: String,
+    entity: &'static str,
+    id: String,
+}
+
+impl InvalidReference {
+    fn new(path: String, entity: &'static str, id: &str) -> InvalidReference {
+        InvalidReference {
+            path,
+            entity,
+            id: id.to_owned(),
+        }
+    }
+
+    /// Getter for `path` field
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Kind of entity that is referenced, like "rollout" or "audience"
+    pub fn entity(&self) -> &str {
+        self.entity
+    }
+
+    /// Getter for `id` field
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl fmt::Display for InvalidReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} refers to {} `{}`, which does not exist", self.path, self.entity, self.id)
+    }
+}
+
+// Collect every reference that makes decisions impossible, sorted by path
+//
+// Empty ids are not references, they are used for flags without a rollout.
+pub(super) fn invalid_references(environment: &Environment) -> Vec<InvalidReference> {
+    let mut violations = Vec::new();
+
+    for flag in environment.feature_flags().values() {
+        let rollout_id = flag.rollout_id();
+        if !rollout_id.is_empty() && !environment.rollouts().contains_key(rollout_id) {
+            let path = format!("featureFlags[\"{}\"].rolloutId", flag.key());
+            violations.push(InvalidReference::new(path, "rollout", rollout_id));
+        }
+    }
+
+    for_each_rule(environment, |path, experiment| {
+        for variation_id in experiment.traffic_allocation().variation_ids() {
+            if !variation_id.is_empty() && experiment.variation(variation_id).is_none() {
+                violations.push(InvalidReference::new(format!("{path}.trafficAllocation"), "variation", variation_id));
+            }
+        }
+    });
+
+    violations.sort();
+    violations
+}
+
+// Collect every other reference to an entity that does not exist, sorted by path
+//
+// These do not stop a datafile from working: a missing audience never matches and events are sent regardless.
+// Flags skip missing experiments, which includes those of mutual exclusion groups, as `groups` is not parsed.
+pub(super) fn dangling_references(environment: &Environment) -> Vec<InvalidReference> {
+    let mut references = Vec::new();
+
+    for flag in environment.feature_flags().values() {
+        for experiment_id in flag.experiments_ids() {
+            if !environment.experiments().contains_key(experiment_id) {
+                let path = format!("featureFlags[\"{}\"].experimentIds", flag.key());
+                references.push(InvalidReference::new(path, "experiment", experiment_id));
+            }
+        }
+    }
+
+    for_each_rule(environment, |path, experiment| {
+        for audience_id in experiment.audience_ids() {
+            if !environment.audiences().contains_key(audience_id) {
+                references.push(InvalidReference::new(format!("{path}.audienceIds"), "audience", audience_id));
+            }
+        }
+
+        if let Some(conditions) = experiment.audience_conditions() {
+            let mut audience_ids = Vec::new();
+            collect_leaves(conditions, &mut audience_ids);
+            for audience_id in audience_ids {
+                if !environment.audiences().contains_key(audience_id) {
+                    let path = format!("{path}.audienceConditions");
+                    references.push(InvalidReference::new(path, "audience", audience_id));
+                }
+            }
+        }
+    });
+
+    // Events can be tied to experiments as well as rollout rules
+    let experiment_ids = environment
+        .experiments()
+        .keys()
+        .map(String::as_str)
+        .chain(
+            environment
+                .rollouts()
+                .values()
+                .flat_map(|rollout| rollout.experiments())
+                .map(|experiment| experiment.id()),
+        )
+        .collect::<HashSet<_>>();
+    for event in environment.events().values() {
+        for experiment_id in event.experiment_ids() {
+            if !experiment_ids.contains(experiment_id.as_str()) {
+                let path = format!("events[\"{}\"].experimentIds", event.key());
+                references.push(InvalidReference::new(path, "experiment", experiment_id));
+            }
+        }
+    }
+
+    references.sort();
+    references
+}
+
+// Call the function with the path of every experiment and rollout rule
+fn for_each_rule<F>(environment: &Environment, mut f: F)
+where
+    F: FnMut(&str, &Experiment),
+{
+    for experiment in environment.experiments().values() {
+        f(&format!("experiments[\"{}\"]", experiment.key()), experiment);
+    }
+
+    for rollout in environment.rollouts().values() {
+        for experiment in rollout.experiments() {
+            f(&format!("rollouts[\"{}\"].experiments[\"{}\"]", rollout.id(), experiment.key()), experiment);
+        }
+    }
+}
+
+// Collect the leaf conditions, in the order they appear
+pub(super) fn collect_leaves<'a, T>(condition: &'a BooleanCondition<T>, leaves: &mut Vec<&'a T>) {
+    match condition {
+        BooleanCondition::And(conditions) | BooleanCondition::Or(conditions) => {
+            for condition in conditions {
+                collect_leaves(condition, leaves);
+            }
+        }
+        BooleanCondition::Not(condition) => {
+            if let Some(condition) = condition {
+                collect_leaves(condition, leaves);
+            }
+        }
+        BooleanCondition::Single(audience_id) => leaves.push(audience_id),
+    }
+}
@@ -0,0 +1,101 @@
+//! Variables of a feature flag
+
+// External imports
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A variable defined on a feature flag.
+///
+/// The datafile stores every value as a string, the `type` field describes how to interpret it.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Variable {
+    #[serde()]
+    id: String,
+    #[serde()]
+    key: String,
+    #[serde(rename = "type")]
+    variable_type: String,
+    #[serde(rename = "subType", default, skip_serializing_if = "Option::is_none")]
+    sub_type: Option<String>,
+    #[serde(rename = "defaultValue")]
+    default_value: String,
+}
+
+impl Variable {
+    /// Getter for `id` field
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Getter for `key` field
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Type of the variable, with the legacy "string" plus "json" subtype mapped to "json"
+    pub fn variable_type(&self) -> &str {
+        match (self.variable_type.as_str(), self.sub_type.as_deref()) {
+            ("string", Some("json")) => "json",
+            (variable_type, _) => variable_type,
+        }
+    }
+
+    /// Getter for `default_value` field
+    pub fn default_value(&self) -> &str {
+        &self.default_value
+    }
+
+    /// Convert a raw string value from the datafile into a JSON value according to the variable type
+    pub fn parse(&self, raw: &str) -> Value {
+        let parsed = match self.variable_type() {
+            "boolean" => raw.parse::<bool>().ok().map(Value::from),
+            "integer" => raw.parse::<i64>().ok().map(Value::from),
+            "double" => raw.parse::<f64>().ok().map(Value::from),
+            "json" => serde_json::from_str(raw).ok(),
+            _ => Some(Value::from(raw)),
+        };
+
+        parsed.unwrap_or_else(|| {
+            log::warn!("Unable to parse value of variable `{}` as {}", self.key, self.variable_type());
+            Value::Null
+        })
+    }
+}
+
+/// Method to deserialize the array of variable values of a variation into a Hashmap from variable id to value
+pub fn deserialize_values<'de, D>(deserializer: D) -> Result<HashMap<String, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct VariableValue {
+        id: String,
+        value: String,
+    }
+
+    let map = Vec::<VariableValue>::deserialize(deserializer)?
+        .into_iter()
+        .map(|variable| (variable.id, variable.value))
+        .collect();
+    Ok(map)
+}
+
+/// Method to serialize the Hashmap of variable values back into an array, sorted by variable id
+pub fn serialize_values<S>(values: &HashMap<String, String>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    #[derive(Serialize)]
+    struct VariableValue<'a> {
+        id: &'a str,
+        value: &'a str,
+    }
+
+    let mut values = values
+        .iter()
+        .map(|(id, value)| VariableValue { id, value })
+        .collect::<Vec<_>>();
+    values.sort_by_key(|variable| variable.id);
+    serializer.collect_seq(values)
+}
@@ -1,6 +1,9 @@
+//! Variations of an experiment or rollout rule
+
 // External imports
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// A single variation like "off", "on" or other user-created variations.
 ///
@@ -9,14 +12,21 @@ use std::collections::HashMap;
 /// The `key` is a human-readable value.
 /// The value of `is_feature_enabled` is `false` for the "off" variation.
 /// All other variations will have `is_feature_enabled` is `true`.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Variation {
     #[serde()]
     id: String,
+    // Shared with every decision for this variation, so a decision does not copy the key
     #[serde()]
-    key: String,
+    key: Arc<str>,
     #[serde(rename = "featureEnabled", default = "default_as_true")]
     is_feature_enabled: bool,
+    #[serde(
+        default,
+        deserialize_with = "super::variable::deserialize_values",
+        serialize_with = "super::variable::serialize_values"
+    )]
+    variables: HashMap<String, String>,
 }
 
 fn default_as_true() -> bool {
@@ -24,14 +34,16 @@ fn default_as_true() -> bool {
 }
 
 impl Variation {
-    // Method to deserialize an array of Variations into a Hashmap of Variations
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<String, Variation>, D::Error>
+    /// Method to deserialize an array of Variations into a Hashmap of Variations
+    ///
+    /// The variations are reference counted, so the traffic allocation can point directly at its variations
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<String, Arc<Variation>>, D::Error>
     where
         D: Deserializer<'de>,
     {
         let mut map = HashMap::new();
         for variation in Vec::<Variation>::deserialize(deserializer)? {
-            map.insert(variation.id.clone(), variation);
+            map.insert(variation.id.clone(), Arc::new(variation));
         }
         Ok(map)
     }
@@ -48,9 +60,19 @@ impl Variation {
         &self.key
     }
 
+    /// Shared reference to the key, which can be kept without copying it
+    pub(crate) fn shared_key(&self) -> &Arc<str> {
+        &self.key
+    }
+
     /// Getter for `is_feature_enabled` field
     #[allow(dead_code)]
     pub fn is_feature_enabled(&self) -> bool {
         self.is_feature_enabled
     }
+
+    /// Raw value of a variable in this variation, by variable id
+    pub fn variable_value(&self, variable_id: &str) -> Option<&str> {
+        self.variables.get(variable_id).map(String::as_str)
+    }
 }
@@ -1,33 +1,162 @@
 //! Result of a feature flag
 
+// External imports
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::sync::{Arc, OnceLock};
+
+// Imports from crate
+use crate::client::{deserialize_attributes, UserAttributes};
+
 // Relative imports of sub modules
 pub use decide_options::DecideOptions;
+pub use rule_type::RuleType;
+
 mod decide_options;
+mod rule_type;
 
 /// Decision for a specfic user and feature flag
-#[derive(Debug)]
+///
+/// The decision borrows the flag key it was made for.
+/// Use `Decision::into_owned` to get a `Decision<'static>` that can be stored or sent to other threads.
+///
+/// A decision can be serialized, for example to return it from an API or to cache it.
+/// The JSON representation has the following shape, where every field is always present:
+///
+/// ```json
+/// {
+///   "flagKey": "sorting_algorithm",
+///   "enabled": true,
+///   "variationKey": "variation_1",
+///   "ruleKey": "sorting_algorithm_experiment",
+///   "variables": { "direction": "desc", "field": "price", "number_of_products": 4 },
+///   "reasons": [],
+///   "userContext": {
+///     "userId": "123abc789xyz",
+///     "attributes": { "is_employee": "true" }
+///   }
+/// }
+/// ```
+///
+/// - `ruleKey` is `null` when the user did not qualify for any experiment or rollout rule.
+/// - `variables` maps each variable key of the flag to its typed value (string, number, boolean or JSON).
+///   It is empty when the `exclude_variables` option was set.
+/// - `reasons` contains log messages when the `include_reasons` option was set.
+///   Critical errors, such as an unknown flag key, are always included.
+///
+/// Apart from reasons and decision events, making a decision does not allocate.
+/// The keys and variables are shared with the datafile,
+/// and the user id and attributes are shared with the user context until either of them is changed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Decision<'a> {
-    flag_key: &'a str,
+    flag_key: Cow<'a, str>,
     enabled: bool,
-    variation_key: String,
+    variation_key: Arc<str>,
+    rule_key: Option<Arc<str>>,
+    variables: Arc<Map<String, Value>>,
+    reasons: Vec<String>,
+    user_context: UserContextSnapshot,
+}
+
+/// Copy of the user id and attributes at the time a decision was made
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserContextSnapshot {
+    user_id: Arc<str>,
+    #[serde(deserialize_with = "deserialize_shared_attributes")]
+    attributes: Arc<UserAttributes>,
+}
+
+fn deserialize_shared_attributes<'de, D>(deserializer: D) -> Result<Arc<UserAttributes>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_attributes(deserializer).map(Arc::new)
+}
+
+impl UserContextSnapshot {
+    // Shares the user id and attributes with the user context instead of copying them
+    pub(crate) fn new(user_id: &Arc<str>, attributes: &Arc<UserAttributes>) -> UserContextSnapshot {
+        UserContextSnapshot {
+            user_id: Arc::clone(user_id),
+            attributes: Arc::clone(attributes),
+        }
+    }
+
+    /// Get the id of the user
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    /// Get the attributes of the user
+    pub fn attributes(&self) -> &UserAttributes {
+        &self.attributes
+    }
+}
+
+// Values shared by every decision without a variation, so those decisions do not allocate either
+fn off_variation_key() -> Arc<str> {
+    static OFF: OnceLock<Arc<str>> = OnceLock::new();
+    Arc::clone(OFF.get_or_init(|| Arc::from("off")))
+}
+
+fn no_variables() -> Arc<Map<String, Value>> {
+    static EMPTY: OnceLock<Arc<Map<String, Value>>> = OnceLock::new();
+    Arc::clone(EMPTY.get_or_init(|| Arc::new(Map::new())))
 }
 
 impl Decision<'_> {
-    pub(crate) fn new<T: Into<String>>(flag_key: &str, enabled: bool, variation_key: T) -> Decision {
+    pub(crate) fn new<T: Into<Arc<str>>>(
+        flag_key: &str, enabled: bool, variation_key: T, user_context: UserContextSnapshot,
+    ) -> Decision<'_> {
         Decision {
-            flag_key,
+            flag_key: Cow::Borrowed(flag_key),
             enabled,
             variation_key: variation_key.into(),
+            rule_key: None,
+            variables: no_variables(),
+            reasons: Vec::new(),
+            user_context,
         }
     }
 
-    pub(crate) fn off(flag_key: &str) -> Decision {
-        Decision::new(flag_key, false, "off")
+    pub(crate) fn off(flag_key: &str, user_context: UserContextSnapshot) -> Decision<'_> {
+        Decision::new(flag_key, false, off_variation_key(), user_context)
+    }
+
+    pub(crate) fn with_rule_key(mut self, rule_key: Option<Arc<str>>) -> Self {
+        self.rule_key = rule_key;
+        self
+    }
+
+    pub(crate) fn with_variables(mut self, variables: Arc<Map<String, Value>>) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    pub(crate) fn with_reasons(mut self, reasons: Vec<String>) -> Self {
+        self.reasons = reasons;
+        self
+    }
+
+    /// Convert into a decision that owns all of its data
+    pub fn into_owned(self) -> Decision<'static> {
+        Decision {
+            flag_key: Cow::Owned(self.flag_key.into_owned()),
+            enabled: self.enabled,
+            variation_key: self.variation_key,
+            rule_key: self.rule_key,
+            variables: self.variables,
+            reasons: self.reasons,
+            user_context: self.user_context,
+        }
     }
 
     /// Get the flag key for which this decision was made
     pub fn flag_key(&self) -> &str {
-        self.flag_key
+        &self.flag_key
     }
 
     /// Get whether the flag should be enabled or disable
@@ -39,4 +168,24 @@ impl Decision<'_> {
     pub fn variation_key(&self) -> &str {
         &self.variation_key
     }
+
+    /// Get the key of the experiment or rollout rule that was used, if any
+    pub fn rule_key(&self) -> Option<&str> {
+        self.rule_key.as_deref()
+    }
+
+    /// Get the values of the flag variables for the decided variation
+    pub fn variables(&self) -> &Map<String, Value> {
+        &self.variables
+    }
+
+    /// Get the reasons that were collected while making the decision
+    pub fn reasons(&self) -> &[String] {
+        &self.reasons
+    }
+
+    /// Get the user id and attributes the decision was made for
+    pub fn user_context(&self) -> &UserContextSnapshot {
+        &self.user_context
+    }
 }
@@ -0,0 +1,16 @@
+// External imports
+use serde::{Deserialize, Serialize};
+
+/// Type of the rule that resulted in a decision
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleType {
+    /// Experiment that is activated directly, outside of a feature flag
+    #[serde(rename = "experiment")]
+    Experiment,
+    /// Rule within the rollout of a feature flag
+    #[serde(rename = "rollout")]
+    Rollout,
+    /// Experiment rule of a feature flag
+    #[serde(rename = "feature-test")]
+    FeatureTest,
+}
@@ -1,17 +1,33 @@
 //! Event logging to Optimizely Event API
 
 // Relative imports of sub modules
-pub use batched_event_dispatcher::BatchedEventDispatcher;
+#[cfg(feature = "async")]
+pub use async_batched_event_dispatcher::AsyncBatchedEventDispatcher;
+#[cfg(feature = "async")]
+pub(crate) use async_event_dispatcher::AsyncEventDispatcherAdapter;
+#[cfg(feature = "async")]
+pub use async_event_dispatcher::{AsyncEventDispatcher, CloseFuture};
+pub use batched_event_dispatcher::{BatchedEventDispatcher, DropListener, OverflowPolicy};
 pub use client::EventApiClient;
+pub use close_report::CloseReport;
 pub use error::EventApiError;
 pub use event::Event;
+pub use request::DecisionMetadata;
 pub use simple_event_dispatcher::SimpleEventDispatcher;
+pub use stats::DispatcherStats;
+pub(crate) use stats::StatsCounters;
 pub use trait_event_dispatcher::EventDispatcher;
 
+#[cfg(feature = "async")]
+mod async_batched_event_dispatcher;
+#[cfg(feature = "async")]
+mod async_event_dispatcher;
 mod batched_event_dispatcher;
 mod client;
+mod close_report;
 mod error;
 mod event;
 pub mod request;
 mod simple_event_dispatcher;
+mod stats;
 mod trait_event_dispatcher;
@@ -0,0 +1,347 @@
+// External imports
+use std::future;
+use std::mem;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::Duration;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::{self, JoinHandle};
+use tokio::time::{self, MissedTickBehavior};
+
+// Imports from super
+use super::batched_event_dispatcher::DEFAULT_CAPACITY;
+use super::{
+    request::Payload, AsyncEventDispatcher, CloseFuture, DispatcherStats, DropListener, Event, EventApiClient,
+    OverflowPolicy, StatsCounters,
+};
+
+// Upper limit to number of events in a batch
+const DEFAULT_BATCH_THRESHOLD: usize = 10;
+
+// Events that do not fill a batch are sent after this interval
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Implementation of the AsyncEventDispatcher trait that collects events in a tokio task
+///
+/// A batch is sent once it contains ten events, or when the flush interval has passed.
+/// Requests to the Event API are made on the blocking thread pool of the runtime, and retried twice when they fail.
+/// Events wait in a queue of at most ten thousand events, and new events are dropped when it is full.
+/// Use `AsyncBatchedEventDispatcher::with_queue` to choose a different capacity or `OverflowPolicy`.
+///
+/// ```
+/// use optimizely::event_api::{AsyncBatchedEventDispatcher, AsyncEventDispatcher, Event};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// // The dispatcher has to be created within a tokio runtime
+/// let dispatcher = AsyncBatchedEventDispatcher::new();
+///
+/// // Sending an event never blocks
+/// let event = Event::decision("21537940595", "user0", "9300000133039", "9300000169122", "87757");
+/// dispatcher.send_event(event);
+///
+/// // Wait until the remaining events are sent
+/// dispatcher.close().await;
+/// # }
+/// ```
+pub struct AsyncBatchedEventDispatcher {
+    transmitter: Mutex<Option<mpsc::Sender<Event>>>,
+    // Shared with the task, so the oldest event can be taken out of a full queue
+    receiver: Arc<Mutex<mpsc::Receiver<Event>>>,
+    policy: OverflowPolicy,
+    task_handle: Mutex<Option<JoinHandle<()>>>,
+    shared: Arc<Shared>,
+}
+
+// State shared between the dispatcher and its task
+#[derive(Default)]
+struct Shared {
+    stats: StatsCounters,
+    // Can be replaced after the task is started, so it is read again for every batch
+    api_client: RwLock<Arc<EventApiClient>>,
+    // Also set after the task is started, and called by the task for events it could not send
+    drop_listener: RwLock<Option<DropListener>>,
+}
+
+impl Shared {
+    fn api_client(&self) -> Arc<EventApiClient> {
+        let api_client = self
+            .api_client
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Arc::clone(&api_client)
+    }
+
+    fn drop_listener(&self) -> Option<DropListener> {
+        self.drop_listener
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    // Pass events that are already counted as dropped to the listener
+    fn notify_dropped(&self, events: &[Event]) {
+        if let Some(listener) = self.drop_listener() {
+            events.iter().for_each(|event| listener(event));
+        }
+    }
+}
+
+impl AsyncBatchedEventDispatcher {
+    /// Spawn a new batched event dispatcher on the current tokio runtime
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside of a tokio runtime.
+    pub fn new() -> AsyncBatchedEventDispatcher {
+        AsyncBatchedEventDispatcher::with_flush_interval(DEFAULT_FLUSH_INTERVAL)
+    }
+
+    /// Spawn a new batched event dispatcher that sends incomplete batches after the given interval
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside of a tokio runtime.
+    pub fn with_flush_interval(flush_interval: Duration) -> AsyncBatchedEventDispatcher {
+        AsyncBatchedEventDispatcher::with_queue(DEFAULT_CAPACITY, OverflowPolicy::DropNewest, flush_interval)
+    }
+
+    /// Spawn a new batched event dispatcher with a queue of the given capacity
+    ///
+    /// With `OverflowPolicy::Block`, sending an event waits for room in the queue on a multi-threaded runtime.
+    /// A runtime with a single thread can not empty the queue meanwhile, so the event is dropped instead.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use optimizely::event_api::{AsyncBatchedEventDispatcher, OverflowPolicy};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// // Keep the most recent thousand events when the Event API can not keep up
+    /// let dispatcher = AsyncBatchedEventDispatcher::with_queue(1_000, OverflowPolicy::DropOldest, Duration::from_secs(30))
+    ///     .with_drop_listener(|event| log::warn!("Dropped event of user {}", event.user_id()));
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside of a tokio runtime.
+    pub fn with_queue(
+        capacity: usize, policy: OverflowPolicy, flush_interval: Duration,
+    ) -> AsyncBatchedEventDispatcher {
+        // A queue without room would block forever or drop everything
+        let (transmitter, receiver) = mpsc::channel::<Event>(capacity.max(1));
+        let receiver = Arc::new(Mutex::new(receiver));
+        let shared = Arc::new(Shared::default());
+
+        let task_receiver = Arc::clone(&receiver);
+        let task_shared = Arc::clone(&shared);
+
+        let task_handle = tokio::spawn(async move {
+            let mut batch = Vec::new();
+
+            let mut ticker = time::interval(flush_interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                // The lock is only held while polling, so senders can take events out of a full queue
+                let event = future::poll_fn(|context| lock(&task_receiver).poll_recv(context));
+
+                tokio::select! {
+                    event = event => match event {
+                        Some(event) => {
+                            batch.push(event);
+                            if batch.len() >= DEFAULT_BATCH_THRESHOLD {
+                                log::debug!("Reached DEFAULT_BATCH_THRESHOLD");
+                                send_batch(mem::take(&mut batch), &task_shared).await;
+                            }
+                        }
+                        // All transmitters are dropped, so no more events will arrive
+                        None => break,
+                    },
+                    _ = ticker.tick() => {
+                        send_batch(mem::take(&mut batch), &task_shared).await;
+                    }
+                }
+            }
+
+            // Send one last batch before stopping
+            send_batch(batch, &task_shared).await;
+        });
+
+        AsyncBatchedEventDispatcher {
+            transmitter: Mutex::new(Some(transmitter)),
+            receiver,
+            policy,
+            task_handle: Mutex::new(Some(task_handle)),
+            shared,
+        }
+    }
+
+    /// Send the batches with the given Event API client, for example to compress the request bodies
+    pub fn with_api_client(self, api_client: EventApiClient) -> AsyncBatchedEventDispatcher {
+        *self
+            .shared
+            .api_client
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(api_client);
+        self
+    }
+
+    /// Call the listener with every event that is dropped instead of sent to the Event API
+    ///
+    /// Events are dropped because the queue is full or already closed, or because every request to send them failed.
+    /// The listener is called on the blocking thread pool of the runtime for the last one.
+    pub fn with_drop_listener<F>(self, listener: F) -> AsyncBatchedEventDispatcher
+    where
+        F: Fn(&Event) + Send + Sync + 'static,
+    {
+        *self
+            .shared
+            .drop_listener
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(listener));
+        self
+    }
+
+    fn drop_event(&self, event: &Event, reason: &str) {
+        log::warn!("Dropped event of user `{}`, because {reason}", event.user_id());
+        self.shared.stats.record_dropped(1);
+        self.shared.notify_dropped(std::slice::from_ref(event));
+    }
+
+    // Wait for room in the queue, as long as the task can keep running meanwhile
+    fn send_blocking(&self, transmitter: &mpsc::Sender<Event>, event: Event) {
+        let rejected = match Handle::try_current() {
+            Ok(handle) => match handle.runtime_flavor() {
+                RuntimeFlavor::MultiThread => task::block_in_place(|| transmitter.blocking_send(event).err()),
+                _ => {
+                    self.drop_event(&event, "the queue is full and the runtime has a single thread");
+                    return;
+                }
+            },
+            Err(_) => transmitter.blocking_send(event).err(),
+        };
+
+        if let Some(mpsc::error::SendError(event)) = rejected {
+            self.drop_event(&event, "the event dispatcher is closed");
+        }
+    }
+
+    // Drop the event that has been waiting the longest, until there is room for the given event
+    fn replace_oldest(&self, transmitter: &mpsc::Sender<Event>, mut event: Event) {
+        let mut receiver = lock(&self.receiver);
+        loop {
+            if let Ok(oldest) = receiver.try_recv() {
+                self.drop_event(&oldest, "the queue is full");
+            }
+
+            match transmitter.try_send(event) {
+                Ok(()) => return,
+                Err(TrySendError::Full(rejected)) => event = rejected,
+                Err(TrySendError::Closed(rejected)) => {
+                    self.drop_event(&rejected, "the event dispatcher is closed");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl Default for AsyncBatchedEventDispatcher {
+    fn default() -> AsyncBatchedEventDispatcher {
+        AsyncBatchedEventDispatcher::new()
+    }
+}
+
+impl AsyncEventDispatcher for AsyncBatchedEventDispatcher {
+    fn send_event(&self, event: Event) {
+        // Counted before it is queued, so the task never sends more events than were enqueued
+        self.shared.stats.record_enqueued();
+
+        // Cloned, so closing the dispatcher does not wait for a sender that is blocked
+        let transmitter = match lock(&self.transmitter).clone() {
+            Some(transmitter) => transmitter,
+            None => {
+                self.drop_event(&event, "the event dispatcher is closed");
+                return;
+            }
+        };
+
+        match transmitter.try_send(event) {
+            Ok(()) => {
+                log::debug!("Successfully sent message to task");
+            }
+            Err(TrySendError::Full(event)) => match self.policy {
+                OverflowPolicy::Block => self.send_blocking(&transmitter, event),
+                OverflowPolicy::DropNewest => self.drop_event(&event, "the queue is full"),
+                OverflowPolicy::DropOldest => self.replace_oldest(&transmitter, event),
+            },
+            Err(TrySendError::Closed(event)) => {
+                self.drop_event(&event, "the event dispatcher is closed");
+            }
+        }
+    }
+
+    fn close(&self) -> CloseFuture<'_> {
+        // Drop the transmitter, so the task will send its last batch and stop
+        drop(lock(&self.transmitter).take());
+        let task_handle = lock(&self.task_handle).take();
+
+        Box::pin(async move {
+            if let Some(task_handle) = task_handle {
+                if task_handle.await.is_err() {
+                    log::error!("Event dispatcher task failed");
+                }
+            }
+        })
+    }
+
+    fn stats(&self) -> Option<DispatcherStats> {
+        Some(self.shared.stats.snapshot())
+    }
+}
+
+// Make a single request to the Event API for all events in the batch
+async fn send_batch(batch: Vec<Event>, shared: &Arc<Shared>) {
+    if batch.is_empty() {
+        log::debug!("No log payload to send");
+        return;
+    }
+
+    let event_count = batch.len();
+
+    // Copies of the events, only kept when a drop listener needs them after a failed request
+    let copies = match shared.drop_listener() {
+        Some(_) => batch.clone(),
+        None => Vec::new(),
+    };
+
+    let thread_shared = Arc::clone(shared);
+    let result = task::spawn_blocking(move || {
+        let mut payload = Payload::new(batch[0].account_id());
+        for event in batch {
+            payload.add_event(event);
+        }
+
+        log::debug!("Sending log payload to Event API");
+        let api_client = thread_shared.api_client();
+        let sent = api_client.send_with_retries(payload, event_count, &thread_shared.stats, || false);
+        if !sent {
+            thread_shared.notify_dropped(&copies);
+        }
+    })
+    .await;
+
+    if result.is_err() {
+        log::error!("Failed to send log payload to Event API");
+        shared.stats.record_dropped(event_count);
+    }
+}
+
+// A panic while holding one of the locks does not leave the inner value in an invalid state
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
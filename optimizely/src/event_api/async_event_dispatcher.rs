@@ -0,0 +1,71 @@
+// External imports
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Imports from super
+use super::{CloseReport, DispatcherStats, Event, EventDispatcher};
+
+/// Future returned by `AsyncEventDispatcher::close`
+pub type CloseFuture<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+/// Trait for event dispatchers that do their work on an async runtime
+///
+/// Decisions are made synchronously, so `send_event` should only hand the event over, for example through a channel.
+/// The actual requests to the Event API are made in the background, and `close` waits until they are done.
+///
+/// ```
+/// use std::sync::Mutex;
+/// use optimizely::event_api::{AsyncEventDispatcher, CloseFuture, Event};
+///
+/// // Dispatcher that keeps events in memory until it is closed
+/// #[derive(Default)]
+/// struct EventStore {
+///     list: Mutex<Vec<Event>>,
+/// }
+///
+/// impl AsyncEventDispatcher for EventStore {
+///     fn send_event(&self, event: Event) {
+///         self.list.lock().unwrap().push(event);
+///     }
+///
+///     fn close(&self) -> CloseFuture<'_> {
+///         Box::pin(async move {
+///             let events = std::mem::take(&mut *self.list.lock().unwrap());
+///             println!("Closing with {} events", events.len());
+///         })
+///     }
+/// }
+/// ```
+pub trait AsyncEventDispatcher: Send + Sync {
+    /// Hand over an event to be sent, without blocking
+    fn send_event(&self, event: Event);
+
+    /// Send all events that were handed over and stop accepting new events
+    fn close(&self) -> CloseFuture<'_>;
+
+    /// Counters of the events handled by this dispatcher, if the dispatcher keeps track of them
+    fn stats(&self) -> Option<DispatcherStats> {
+        None
+    }
+}
+
+// The client only knows about EventDispatcher, so the async dispatcher is wrapped when it is used by the client
+pub(crate) struct AsyncEventDispatcherAdapter(pub(crate) Arc<dyn AsyncEventDispatcher>);
+
+impl EventDispatcher for AsyncEventDispatcherAdapter {
+    fn send_event(&self, event: Event) {
+        self.0.send_event(event);
+    }
+
+    fn close(&self, _timeout: Duration) -> CloseReport {
+        // Blocking on the future could deadlock the runtime, so async dispatchers are only closed by close_async
+        log::warn!("Use Client::close_async to close an async event dispatcher");
+        CloseReport::default()
+    }
+
+    fn stats(&self) -> Option<DispatcherStats> {
+        self.0.stats()
+    }
+}
@@ -1,18 +1,43 @@
 // External imports
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
 use std::thread;
+use std::time::Duration;
 
 // Imports from super
-use super::{Event, EventDispatcher};
+use super::{CloseReport, DispatcherStats, Event, EventApiClient, EventDispatcher, StatsCounters};
 
 // Relative imports of sub modules
 use batched_payload::BatchedPayload;
+use event_queue::{EventQueue, Push};
 
 mod batched_payload;
+mod event_queue;
+
+// Time to wait for the last batch when the dispatcher is dropped without being closed
+const DEFAULT_DROP_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Number of events that can be waiting to be sent by default
+pub(super) const DEFAULT_CAPACITY: usize = 10_000;
+
+/// Function that is called with every event that is dropped instead of sent to the Event API
+pub type DropListener = Arc<dyn Fn(&Event) + Send + Sync>;
+
+/// What a batched event dispatcher does when its queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait until the queue has room again, which also blocks the decision that sent the event
+    Block,
+    /// Drop the event that is being sent
+    DropNewest,
+    /// Drop the event that has been waiting the longest, to make room for the event that is being sent
+    DropOldest,
+}
 
 /// Implementation of the EventDisptacher trait that collects multiple events before sending them
 ///
 /// ```
+/// use std::time::Duration;
 /// use optimizely::event_api::{BatchedEventDispatcher, Event, EventDispatcher};
 ///
 /// // Create some example IDs
@@ -37,69 +62,213 @@ mod batched_payload;
 ///     dispatcher.send_event(event);
 /// }
 ///
+/// // Send the last batch, but do not wait longer than five seconds
+/// let report = dispatcher.close(Duration::from_secs(5));
+/// assert_eq!(report.sent + report.dropped, 3);
+///
 /// // Note that only one request will be sent to the Event API
 /// ```
 ///
-/// Inspiration from [Spawn threads and join in destructor](https://users.rust-lang.org/t/spawn-threads-and-join-in-destructor/1613/9)
+/// Events wait in a queue of at most ten thousand events, and new events are dropped when it is full.
+/// Use `BatchedEventDispatcher::new` to choose a different capacity or `OverflowPolicy`.
+/// Failed requests are retried twice before the events in the batch are dropped.
+/// Dropping the dispatcher without closing it first, waits for the last batch for at most ten seconds.
 pub struct BatchedEventDispatcher {
-    thread_handle: Option<thread::JoinHandle<()>>,
-    transmitter: Option<mpsc::Sender<Event>>,
+    thread_handle: Mutex<Option<thread::JoinHandle<()>>>,
+    queue: Arc<EventQueue>,
+    finished: Mutex<Option<mpsc::Receiver<()>>>,
+    shared: Arc<Shared>,
+}
+
+// State shared between the dispatcher and its thread
+#[derive(Default)]
+struct Shared {
+    stats: StatsCounters,
+    // Set when closing timed out, after which the thread drops its batches instead of sending them
+    abandoned: AtomicBool,
+    // Can be replaced after the thread is started, so it is read again for every batch
+    api_client: RwLock<Arc<EventApiClient>>,
+    // Also set after the thread is started, and called by the thread for events it could not send
+    drop_listener: RwLock<Option<DropListener>>,
+}
+
+impl Shared {
+    fn api_client(&self) -> Arc<EventApiClient> {
+        let api_client = self
+            .api_client
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Arc::clone(&api_client)
+    }
+
+    fn drop_listener(&self) -> Option<DropListener> {
+        self.drop_listener
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    // Pass events that are already counted as dropped to the listener
+    fn notify_dropped(&self, events: &[Event]) {
+        if let Some(listener) = self.drop_listener() {
+            events.iter().for_each(|event| listener(event));
+        }
+    }
 }
 
 impl Default for BatchedEventDispatcher {
     /// Constructor for a new batched event dispatcher
     fn default() -> BatchedEventDispatcher {
-        let (transmitter, receiver) = mpsc::channel();
+        BatchedEventDispatcher::new(DEFAULT_CAPACITY, OverflowPolicy::DropNewest)
+    }
+}
 
+impl BatchedEventDispatcher {
+    /// Constructor for a batched event dispatcher with a queue of the given capacity
+    ///
+    /// ```
+    /// use optimizely::event_api::{BatchedEventDispatcher, OverflowPolicy};
+    ///
+    /// // Keep the most recent thousand events when the Event API can not keep up
+    /// let dispatcher = BatchedEventDispatcher::new(1_000, OverflowPolicy::DropOldest)
+    ///     .with_drop_listener(|event| log::warn!("Dropped event of user {}", event.user_id()));
+    /// ```
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> BatchedEventDispatcher {
+        let queue = Arc::new(EventQueue::new(capacity, policy));
+        let (finished_transmitter, finished_receiver) = mpsc::channel();
+        let shared = Arc::new(Shared::default());
+
+        let thread_queue = Arc::clone(&queue);
+        let thread_shared = Arc::clone(&shared);
         let thread_handle = thread::spawn(move || {
-            let mut batched_payload = BatchedPayload::new();
+            let mut batched_payload = BatchedPayload::new(thread_shared);
 
-            // Keep receiving new message from the main thread
-            for event in receiver.iter() {
+            // Keep receiving new events from the queue until it is closed
+            while let Some(event) = thread_queue.pop() {
                 batched_payload.add_event(event);
             }
+
+            // Send the last batch and signal that the thread is done
+            batched_payload.flush();
+            let _ = finished_transmitter.send(());
         });
 
         BatchedEventDispatcher {
-            thread_handle: Some(thread_handle),
-            transmitter: Some(transmitter),
+            thread_handle: Mutex::new(Some(thread_handle)),
+            queue,
+            finished: Mutex::new(Some(finished_receiver)),
+            shared,
         }
     }
+
+    /// Send the batches with the given Event API client, for example to compress the request bodies
+    pub fn with_api_client(self, api_client: EventApiClient) -> BatchedEventDispatcher {
+        *self
+            .shared
+            .api_client
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(api_client);
+        self
+    }
+
+    /// Call the listener with every event that is dropped instead of sent to the Event API
+    ///
+    /// Events are dropped because the queue is full or already closed, because every request to send them failed,
+    /// or because closing the dispatcher timed out.
+    /// The listener is called on the thread of the dispatcher for the last two, which can be after `close` returned.
+    pub fn with_drop_listener<F>(self, listener: F) -> BatchedEventDispatcher
+    where
+        F: Fn(&Event) + Send + Sync + 'static,
+    {
+        *self
+            .shared
+            .drop_listener
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(listener));
+        self
+    }
+
+    fn drop_event(&self, event: &Event, reason: &str) {
+        log::warn!("Dropped event of user `{}`, because {reason}", event.user_id());
+        self.shared.stats.record_dropped(1);
+        self.shared.notify_dropped(std::slice::from_ref(event));
+    }
 }
 
 impl Drop for BatchedEventDispatcher {
     fn drop(&mut self) {
-        // Take the transmitter_decision and replace it with None
-        if let Some(tx) = self.transmitter.take() {
-            // Drop the transmitter first, so the receiver in the thread will eventually stop
-            drop(tx);
-        }
-
-        // Take the thread_handle and replace it with None
-        if let Some(handle) = self.thread_handle.take() {
-            // Wait until the thread has send the last batch
-            let result = handle.join();
-            // Ignore result
-            drop(result);
+        // Does nothing if the dispatcher was already closed
+        let report = self.close(DEFAULT_DROP_TIMEOUT);
+        if report.dropped > 0 {
+            log::warn!("Dropped {} events while dropping the event dispatcher", report.dropped);
         }
     }
 }
 
 impl EventDispatcher for BatchedEventDispatcher {
     fn send_event(&self, event: Event) {
+        // Counted before it is queued, so the thread never sends more events than were enqueued
+        self.shared.stats.record_enqueued();
+
         // Send event to thread
-        match &self.transmitter {
-            Some(tx) => match tx.send(event) {
-                Ok(_) => {
-                    log::debug!("Successfully sent message to thread");
-                }
-                Err(_) => {
-                    log::error!("Failed to send message to thread");
+        match self.queue.push(event) {
+            Push::Queued => {
+                log::debug!("Successfully sent message to thread");
+            }
+            Push::Replaced(oldest) => {
+                self.drop_event(&oldest, "the queue is full");
+            }
+            Push::Rejected(event) => {
+                self.drop_event(&event, "the queue is full");
+            }
+            Push::Closed(event) => {
+                self.drop_event(&event, "the event dispatcher is closed");
+            }
+        }
+    }
+
+    fn close(&self, timeout: Duration) -> CloseReport {
+        let pending = self.shared.stats.queue_depth();
+        let sent_before = self.shared.stats.events_sent();
+
+        // Close the queue first, so the thread will eventually stop
+        self.queue.close();
+
+        // Only the first call to close waits for the thread
+        let finished = match lock(&self.finished).take() {
+            Some(finished) => finished,
+            None => return CloseReport::default(),
+        };
+
+        match finished.recv_timeout(timeout) {
+            Ok(_) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                // The thread is done, so joining will not block
+                if let Some(handle) = lock(&self.thread_handle).take() {
+                    let _ = handle.join();
                 }
-            },
-            None => {
-                log::error!("Transmitter already dropped");
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                // Leave the thread behind, and make sure it does not send anything that is reported as dropped
+                log::warn!("Timeout while sending the last events to the Event API");
+                self.shared.abandoned.store(true, Ordering::SeqCst);
             }
         }
+
+        let sent = self.shared.stats.events_sent() - sent_before;
+        CloseReport {
+            sent: sent as usize,
+            dropped: pending.saturating_sub(sent) as usize,
+        }
+    }
+
+    fn stats(&self) -> Option<DispatcherStats> {
+        Some(self.shared.stats.snapshot())
     }
 }
+
+// A panic while holding one of the locks does not leave the inner value in an invalid state
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -1,5 +1,12 @@
+// External imports
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
 // Imports from crate
-use super::super::{request::Payload, Event, EventApiClient};
+use super::super::{request::Payload, Event};
+
+// Imports from super
+use super::Shared;
 
 // Upper limit to number of events in a batch
 const DEFAULT_BATCH_THRESHOLD: u16 = 10;
@@ -7,20 +14,29 @@ const DEFAULT_BATCH_THRESHOLD: u16 = 10;
 pub(super) struct BatchedPayload<'a> {
     counter: u16,
     payload_option: Option<Payload<'a>>,
+    // Copies of the events in the payload, only kept when a drop listener needs them after a failed request
+    copies: Vec<Event>,
+    shared: Arc<Shared>,
 }
 
 impl BatchedPayload<'_> {
-    pub(super) fn new() -> BatchedPayload<'static> {
+    pub(super) fn new(shared: Arc<Shared>) -> BatchedPayload<'static> {
         let payload_option: Option<Payload> = None;
         let counter = 0;
 
         BatchedPayload {
             counter,
             payload_option,
+            copies: Vec::new(),
+            shared,
         }
     }
 
     pub(super) fn add_event(&mut self, event: Event) {
+        if self.shared.drop_listener().is_some() {
+            self.copies.push(event.clone());
+        }
+
         // Add to the existing payload or create a new one
         match self.payload_option.as_mut() {
             None => {
@@ -44,30 +60,37 @@ impl BatchedPayload<'_> {
 
         if self.counter >= DEFAULT_BATCH_THRESHOLD {
             log::debug!("Reached DEFAULT_BATCH_THRESHOLD");
-            self.send();
+            self.flush();
         }
     }
 
-    fn send(&mut self) {
+    pub(super) fn flush(&mut self) {
         // Take ownership of payload and leave behind None (for next iteration)
         match self.payload_option.take() {
             Some(payload) => {
+                let event_count = usize::from(self.counter);
+
+                // Reset counter
+                self.counter = 0;
+                let copies = std::mem::take(&mut self.copies);
+
+                // Closing the dispatcher timed out, so these events are already reported as dropped
+                if self.shared.abandoned.load(Ordering::SeqCst) {
+                    log::warn!("Dropping log payload after timeout");
+                    self.shared.stats.record_dropped(event_count);
+                    self.shared.notify_dropped(&copies);
+                    return;
+                }
+
                 // Sending payload
                 log::debug!("Sending log payload to Event API");
 
-                // Send payload to endpoint
-                match EventApiClient::send(payload) {
-                    Ok(_) => {
-                        log::info!("Successfull request to Event API");
-                    }
-                    Err(report) => {
-                        log::error!("Failed request to Event API");
-                        log::error!("\n{report:?}");
-                    }
+                // Send payload to endpoint, and stop retrying once closing the dispatcher timed out
+                let abandoned = || self.shared.abandoned.load(Ordering::SeqCst);
+                let api_client = self.shared.api_client();
+                if !api_client.send_with_retries(payload, event_count, &self.shared.stats, abandoned) {
+                    self.shared.notify_dropped(&copies);
                 }
-
-                // Reset counter
-                self.counter = 0;
             }
             None => {
                 // Nothing to send
@@ -82,6 +105,6 @@ impl Drop for BatchedPayload<'_> {
         log::debug!("Dropping BatchedPayload");
 
         // If the BatchedLogPayload is dropped, send one last payload
-        self.send()
+        self.flush()
     }
 }
@@ -0,0 +1,189 @@
+// External imports
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+// Imports from crate
+use super::super::Event;
+
+// Imports from super
+use super::OverflowPolicy;
+
+// Outcome of pushing an event onto the queue
+pub(super) enum Push {
+    // The event was added to the queue
+    Queued,
+    // The event was added, but the given event had to make room for it
+    Replaced(Event),
+    // The event was not added, because the queue was full
+    Rejected(Event),
+    // The event was not added, because the queue was closed
+    Closed(Event),
+}
+
+// Bounded queue between the dispatcher and its thread
+pub(super) struct EventQueue {
+    state: Mutex<State>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize,
+    policy: OverflowPolicy,
+}
+
+struct State {
+    events: VecDeque<Event>,
+    closed: bool,
+}
+
+impl EventQueue {
+    pub(super) fn new(capacity: usize, policy: OverflowPolicy) -> EventQueue {
+        EventQueue {
+            state: Mutex::new(State {
+                events: VecDeque::new(),
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            // A queue without room would block forever or drop everything
+            capacity: capacity.max(1),
+            policy,
+        }
+    }
+
+    pub(super) fn push(&self, event: Event) -> Push {
+        let mut state = self.lock();
+        if state.closed {
+            return Push::Closed(event);
+        }
+
+        if state.events.len() >= self.capacity {
+            match self.policy {
+                OverflowPolicy::Block => {
+                    while state.events.len() >= self.capacity && !state.closed {
+                        state = self
+                            .not_full
+                            .wait(state)
+                            .unwrap_or_else(|poisoned| poisoned.into_inner());
+                    }
+                }
+                OverflowPolicy::DropNewest => return Push::Rejected(event),
+                OverflowPolicy::DropOldest => {
+                    if let Some(oldest) = state.events.pop_front() {
+                        state.events.push_back(event);
+                        return Push::Replaced(oldest);
+                    }
+                }
+            }
+        }
+
+        // Closing also wakes up blocked senders, whose events are not accepted anymore
+        if state.closed {
+            return Push::Closed(event);
+        }
+
+        state.events.push_back(event);
+        self.not_empty.notify_one();
+        Push::Queued
+    }
+
+    // Wait for the next event, returns None once the queue is closed and empty
+    pub(super) fn pop(&self) -> Option<Event> {
+        let mut state = self.lock();
+        loop {
+            if let Some(event) = state.events.pop_front() {
+                self.not_full.notify_one();
+                return Some(event);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self
+                .not_empty
+                .wait(state)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+
+    // Stop accepting events, events already in the queue can still be popped
+    pub(super) fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn event(user_id: &str) -> Event {
+        Event::conversion("21537940595", user_id, "22305150298", "purchase")
+    }
+
+    fn fill(queue: &EventQueue, user_ids: &[&str]) {
+        for user_id in user_ids {
+            assert!(matches!(queue.push(event(user_id)), Push::Queued));
+        }
+    }
+
+    #[test]
+    fn drop_newest() {
+        let queue = EventQueue::new(2, OverflowPolicy::DropNewest);
+        fill(&queue, &["user1", "user2"]);
+
+        match queue.push(event("user3")) {
+            Push::Rejected(event) => assert_eq!(event.user_id(), "user3"),
+            _ => panic!("Event should be rejected"),
+        }
+        assert_eq!(queue.pop().unwrap().user_id(), "user1");
+    }
+
+    #[test]
+    fn drop_oldest() {
+        let queue = EventQueue::new(2, OverflowPolicy::DropOldest);
+        fill(&queue, &["user1", "user2"]);
+
+        match queue.push(event("user3")) {
+            Push::Replaced(event) => assert_eq!(event.user_id(), "user1"),
+            _ => panic!("Oldest event should be replaced"),
+        }
+        assert_eq!(queue.pop().unwrap().user_id(), "user2");
+        assert_eq!(queue.pop().unwrap().user_id(), "user3");
+    }
+
+    #[test]
+    fn block_until_popped() {
+        let queue = Arc::new(EventQueue::new(1, OverflowPolicy::Block));
+        fill(&queue, &["user1"]);
+
+        let sender_queue = Arc::clone(&queue);
+        let sender = thread::spawn(move || matches!(sender_queue.push(event("user2")), Push::Queued));
+
+        // Making room lets the blocked sender continue
+        assert_eq!(queue.pop().unwrap().user_id(), "user1");
+        assert!(sender.join().unwrap());
+        assert_eq!(queue.pop().unwrap().user_id(), "user2");
+    }
+
+    #[test]
+    fn close_wakes_blocked_sender() {
+        let queue = Arc::new(EventQueue::new(1, OverflowPolicy::Block));
+        fill(&queue, &["user1"]);
+
+        let sender_queue = Arc::clone(&queue);
+        let sender = thread::spawn(move || matches!(sender_queue.push(event("user2")), Push::Closed(_)));
+
+        // Events that were queued before closing can still be popped
+        queue.close();
+        assert!(sender.join().unwrap());
+        assert_eq!(queue.pop().unwrap().user_id(), "user1");
+        assert!(queue.pop().is_none());
+    }
+}
@@ -1,29 +1,181 @@
 // External imports
 use error_stack::{IntoReport, Result, ResultExt};
+#[cfg(feature = "gzip")]
+use flate2::{write::GzEncoder, Compression};
+#[cfg(feature = "gzip")]
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
 
 // Imports from super
-use super::{request::Payload, EventApiError};
+use super::{request::Payload, stats::StatsCounters, EventApiError};
 
 // Information about the API endpoint
 const ENDPOINT_URL: &str = "https://logx.optimizely.com/v1/events";
 const CONTENT_TYPE_KEY: &str = "content-type";
 const CONTENT_TYPE_VALUE: &str = "application/json";
+#[cfg(feature = "gzip")]
+const CONTENT_ENCODING_KEY: &str = "content-encoding";
+#[cfg(feature = "gzip")]
+const CONTENT_ENCODING_VALUE: &str = "gzip";
+
+// Number of attempts for a request before the events are dropped
+const MAX_ATTEMPTS: u32 = 3;
+
+// Wait time before the first retry, doubled for every next retry
+const INITIAL_BACKOFF: Duration = Duration::from_millis(200);
 
 /// HTTP client for the Event API
-pub struct EventApiClient {}
+///
+/// By default, uncompressed JSON is sent to the Optimizely Event API.
+/// With the `gzip` feature, the request bodies can be compressed as well.
+///
+/// ```
+/// use optimizely::event_api::{BatchedEventDispatcher, EventApiClient};
+///
+/// // Send the events through a proxy
+/// let api_client = EventApiClient::default().with_endpoint_url("https://events.example.com/v1/events");
+///
+/// let dispatcher = BatchedEventDispatcher::default().with_api_client(api_client);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventApiClient {
+    endpoint_url: String,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+}
+
+impl Default for EventApiClient {
+    fn default() -> EventApiClient {
+        EventApiClient {
+            endpoint_url: String::from(ENDPOINT_URL),
+            #[cfg(feature = "gzip")]
+            gzip: false,
+        }
+    }
+}
 
 impl EventApiClient {
-    /// Serialize the payload to JSON and send to Event API
+    /// Send requests to a different endpoint, for example a proxy in front of the Event API
+    pub fn with_endpoint_url<T: Into<String>>(mut self, endpoint_url: T) -> EventApiClient {
+        self.endpoint_url = endpoint_url.into();
+        self
+    }
+
+    /// Compress request bodies with gzip, and set the `Content-Encoding` header accordingly
+    ///
+    /// ```
+    /// use optimizely::event_api::{BatchedEventDispatcher, EventApiClient};
+    ///
+    /// let api_client = EventApiClient::default().with_gzip(true);
+    /// let dispatcher = BatchedEventDispatcher::default().with_api_client(api_client);
+    /// ```
+    #[cfg(feature = "gzip")]
+    pub fn with_gzip(mut self, gzip: bool) -> EventApiClient {
+        self.gzip = gzip;
+        self
+    }
+
+    /// Getter for `endpoint_url` field
+    pub fn endpoint_url(&self) -> &str {
+        &self.endpoint_url
+    }
+
+    /// Getter for `gzip` field
+    #[cfg(feature = "gzip")]
+    pub fn gzip(&self) -> bool {
+        self.gzip
+    }
+
+    /// Serialize the payload to JSON and send to Event API, using the default endpoint without compression
     pub fn send(payload: Payload) -> Result<(), EventApiError> {
-        // Convert to JSON document and dump as String
-        let body = serde_json::to_string(&payload)
+        EventApiClient::default().send_payload(payload)
+    }
+
+    /// Serialize the payload to JSON and send to the configured endpoint
+    pub fn send_payload(&self, payload: Payload) -> Result<(), EventApiError> {
+        // Convert to JSON document, compressed if needed
+        let body = self.encode(&payload)?;
+
+        // Make POST request
+        self.post(&body)
+    }
+
+    /// Send the payload, retrying failed requests, and record the outcome in the counters
+    ///
+    /// Retries stop early when `give_up` returns true. Returns whether the events were sent.
+    pub(crate) fn send_with_retries<F>(
+        &self, payload: Payload, event_count: usize, stats: &StatsCounters, give_up: F,
+    ) -> bool
+    where
+        F: Fn() -> bool,
+    {
+        let body = match self.encode(&payload) {
+            Ok(body) => body,
+            Err(report) => {
+                log::error!("\n{report:?}");
+                stats.record_dropped(event_count);
+                return false;
+            }
+        };
+
+        let mut backoff = INITIAL_BACKOFF;
+        for attempt in 1..=MAX_ATTEMPTS {
+            match self.post(&body) {
+                Ok(_) => {
+                    log::info!("Successfull request to Event API");
+                    stats.record_success(event_count);
+                    return true;
+                }
+                Err(report) => {
+                    log::error!("Failed request to Event API (attempt {attempt} of {MAX_ATTEMPTS})");
+                    log::error!("\n{report:?}");
+                    stats.record_failure();
+                }
+            }
+
+            if attempt == MAX_ATTEMPTS || give_up() {
+                break;
+            }
+
+            thread::sleep(backoff);
+            backoff *= 2;
+            stats.record_retry();
+        }
+
+        log::warn!("Dropping {event_count} events after failed requests to Event API");
+        stats.record_dropped(event_count);
+        false
+    }
+
+    fn encode(&self, payload: &Payload) -> Result<Vec<u8>, EventApiError> {
+        let json = serde_json::to_vec(payload)
             .into_report()
             .change_context(EventApiError::FailedSerialize)?;
 
-        // Make POST request
-        ureq::post(ENDPOINT_URL)
-            .set(CONTENT_TYPE_KEY, CONTENT_TYPE_VALUE)
-            .send_string(&body)
+        #[cfg(feature = "gzip")]
+        if self.gzip {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            return encoder
+                .write_all(&json)
+                .and_then(|_| encoder.finish())
+                .into_report()
+                .change_context(EventApiError::FailedCompress);
+        }
+
+        Ok(json)
+    }
+
+    fn post(&self, body: &[u8]) -> Result<(), EventApiError> {
+        let request = ureq::post(&self.endpoint_url).set(CONTENT_TYPE_KEY, CONTENT_TYPE_VALUE);
+        #[cfg(feature = "gzip")]
+        let request = match self.gzip {
+            true => request.set(CONTENT_ENCODING_KEY, CONTENT_ENCODING_VALUE),
+            false => request,
+        };
+
+        request
+            .send_bytes(body)
             .into_report()
             .change_context(EventApiError::FailedRequest)?;
 
@@ -0,0 +1,10 @@
+/// Outcome of closing an event dispatcher
+///
+/// Only events that were still pending when the dispatcher was closed are counted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CloseReport {
+    /// Number of pending events that were sent to the Event API
+    pub sent: usize,
+    /// Number of pending events that were not sent, because the request failed or the timeout expired
+    pub dropped: usize,
+}
@@ -10,4 +10,8 @@ pub enum EventApiError {
     #[doc(hidden)]
     #[error("Failed to serialize payload to JSON")]
     FailedSerialize,
+    #[doc(hidden)]
+    #[cfg(feature = "gzip")]
+    #[error("Failed to compress payload")]
+    FailedCompress,
 }
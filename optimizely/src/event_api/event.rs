@@ -1,3 +1,9 @@
+// Imports from crate
+use crate::decision::RuleType;
+
+// Imports from super
+use super::DecisionMetadata;
+
 /// Representation of the events which can be dispatched to Optimizely Event API
 ///
 /// An event can either be a decision or conversion.
@@ -34,7 +40,7 @@
 /// assert_eq!(conversion.account_id(), account_id);
 /// ```
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Event {
     /// An event that indicates a user being bucketed into an experiment
     Decision {
@@ -48,6 +54,8 @@ pub enum Event {
         experiment_id: String,
         #[doc(hidden)]
         variation_id: String,
+        #[doc(hidden)]
+        metadata: DecisionMetadata,
     },
 
     /// An event that indicates a user interacting with the application
@@ -64,9 +72,25 @@ pub enum Event {
 }
 
 impl Event {
-    /// Constructor for a new decision event
+    /// Constructor for a new decision event of an experiment that is not part of a feature flag
     pub fn decision<T: Into<String>>(
         account_id: T, user_id: T, campaign_id: T, experiment_id: T, variation_id: T,
+    ) -> Event {
+        // Without a flag there is little metadata to add
+        let metadata = DecisionMetadata {
+            flag_key: String::new(),
+            rule_key: String::new(),
+            rule_type: RuleType::Experiment,
+            variation_key: String::new(),
+            enabled: false,
+        };
+
+        Event::flag_decision(account_id, user_id, campaign_id, experiment_id, variation_id, metadata)
+    }
+
+    /// Constructor for a new decision event that includes the metadata of the flag decision
+    pub fn flag_decision<T: Into<String>>(
+        account_id: T, user_id: T, campaign_id: T, experiment_id: T, variation_id: T, metadata: DecisionMetadata,
     ) -> Event {
         Event::Decision {
             account_id: account_id.into(),
@@ -74,6 +98,7 @@ impl Event {
             campaign_id: campaign_id.into(),
             experiment_id: experiment_id.into(),
             variation_id: variation_id.into(),
+            metadata,
         }
     }
 
@@ -102,4 +127,12 @@ impl Event {
             Event::Conversion { user_id, .. } => user_id,
         }
     }
+
+    /// Getter for the metadata field that only exists for `Event::Decision`
+    pub fn decision_metadata(&self) -> Option<&DecisionMetadata> {
+        match self {
+            Event::Decision { metadata, .. } => Some(metadata),
+            Event::Conversion { .. } => None,
+        }
+    }
 }
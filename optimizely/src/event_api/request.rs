@@ -2,6 +2,7 @@
 
 // Relative imports of sub modules
 use decision::Decision;
+pub use decision::DecisionMetadata;
 use event::Event;
 pub use payload::Payload;
 use snapshot::Snapshot;
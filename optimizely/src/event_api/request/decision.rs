@@ -1,21 +1,45 @@
 // External imports
 use serde::Serialize;
 
+// Imports from crate
+use crate::decision::RuleType;
+
 #[derive(Serialize)]
 pub struct Decision {
     campaign_id: String,
     experiment_id: String,
     variation_id: String,
     is_campaign_holdback: bool,
+    metadata: DecisionMetadata,
 }
 
 impl Decision {
-    pub fn new(campaign_id: String, experiment_id: String, variation_id: String) -> Decision {
+    pub fn new(
+        campaign_id: String, experiment_id: String, variation_id: String, metadata: DecisionMetadata,
+    ) -> Decision {
         Decision {
             campaign_id,
             experiment_id,
             variation_id,
             is_campaign_holdback: false,
+            metadata,
         }
     }
 }
+
+/// Information about the flag decision that resulted in a decision event
+///
+/// The Event API uses this metadata to attribute impressions to flags and rules on the results page.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DecisionMetadata {
+    /// Key of the feature flag
+    pub flag_key: String,
+    /// Key of the experiment or rollout rule, empty when no rule matched
+    pub rule_key: String,
+    /// Type of the rule
+    pub rule_type: RuleType,
+    /// Key of the variation, empty when no rule matched
+    pub variation_key: String,
+    /// Whether the flag is enabled
+    pub enabled: bool,
+}
@@ -52,6 +52,7 @@ impl Payload<'_> {
                 campaign_id,
                 experiment_id,
                 variation_id,
+                metadata,
                 ..
             } => {
                 log::debug!("Adding decision event to log payload");
@@ -60,7 +61,7 @@ impl Payload<'_> {
                 let entity_id = campaign_id.clone();
 
                 // Add decision to visitor
-                visitor.add_decision(campaign_id, experiment_id, variation_id);
+                visitor.add_decision(campaign_id, experiment_id, variation_id, metadata);
 
                 // Add campaign_activated event
                 visitor.add_event(entity_id, String::from(ACTIVATE_EVENT_KEY));
@@ -79,3 +80,45 @@ impl Payload<'_> {
         self.visitors.push(visitor);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision::RuleType;
+    use crate::event_api::DecisionMetadata;
+    use serde_json::json;
+
+    #[test]
+    fn decision_metadata() {
+        let metadata = DecisionMetadata {
+            flag_key: "buy_button".into(),
+            rule_key: "buy_button_experiment".into(),
+            rule_type: RuleType::FeatureTest,
+            variation_key: "primary".into(),
+            enabled: true,
+        };
+        let event = Event::flag_decision("21537940595", "user0", "9300000093600", "9300000127039", "87755", metadata);
+
+        let mut payload = Payload::new("21537940595");
+        payload.add_event(event);
+
+        // The metadata block is part of the decision
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(
+            json["visitors"][0]["snapshots"][0]["decisions"][0],
+            json!({
+                "campaign_id": "9300000093600",
+                "experiment_id": "9300000127039",
+                "variation_id": "87755",
+                "is_campaign_holdback": false,
+                "metadata": {
+                    "flag_key": "buy_button",
+                    "rule_key": "buy_button_experiment",
+                    "rule_type": "feature-test",
+                    "variation_key": "primary",
+                    "enabled": true
+                }
+            })
+        );
+    }
+}
@@ -2,7 +2,7 @@
 use serde::Serialize;
 
 // Imports from super
-use super::{Decision, Event};
+use super::{Decision, DecisionMetadata, Event};
 
 #[derive(Serialize, Default)]
 pub struct Snapshot {
@@ -15,8 +15,10 @@ impl Snapshot {
         Snapshot::default()
     }
 
-    pub fn add_decision(&mut self, campaign_id: String, experiment_id: String, variation_id: String) {
-        let decision = Decision::new(campaign_id, experiment_id, variation_id);
+    pub fn add_decision(
+        &mut self, campaign_id: String, experiment_id: String, variation_id: String, metadata: DecisionMetadata,
+    ) {
+        let decision = Decision::new(campaign_id, experiment_id, variation_id, metadata);
         self.decisions.push(decision);
     }
 
@@ -2,7 +2,7 @@
 use serde::Serialize;
 
 // Imports from super
-use super::Snapshot;
+use super::{DecisionMetadata, Snapshot};
 
 #[derive(Serialize)]
 pub struct Visitor {
@@ -19,8 +19,10 @@ impl Visitor {
         }
     }
 
-    pub fn add_decision(&mut self, campaign_id: String, experiment_id: String, variation_id: String) {
-        self.snapshots[0].add_decision(campaign_id, experiment_id, variation_id);
+    pub fn add_decision(
+        &mut self, campaign_id: String, experiment_id: String, variation_id: String, metadata: DecisionMetadata,
+    ) {
+        self.snapshots[0].add_decision(campaign_id, experiment_id, variation_id, metadata);
     }
 
     pub fn add_event(&mut self, entity_id: String, event_key: String) {
@@ -1,5 +1,5 @@
 // Imports from super
-use super::{request::Payload, Event, EventApiClient, EventDispatcher};
+use super::{request::Payload, DispatcherStats, Event, EventApiClient, EventDispatcher, StatsCounters};
 
 /// Implementation of the EventDisptacher trait that makes an HTTP request for every event
 ///
@@ -22,12 +22,28 @@ use super::{request::Payload, Event, EventApiClient, EventDispatcher};
 /// // Send single event
 /// dispatcher.send_event(event);
 /// ```
-pub struct SimpleEventDispatcher {}
+///
+/// Failed requests are not retried, since that would block the thread that made the decision.
+pub struct SimpleEventDispatcher {
+    api_client: EventApiClient,
+    stats: StatsCounters,
+}
 
 impl Default for SimpleEventDispatcher {
     /// Constructor for a new simple event dispatcher
     fn default() -> SimpleEventDispatcher {
-        SimpleEventDispatcher {}
+        SimpleEventDispatcher {
+            api_client: EventApiClient::default(),
+            stats: StatsCounters::default(),
+        }
+    }
+}
+
+impl SimpleEventDispatcher {
+    /// Send the events with the given Event API client, for example to compress the request bodies
+    pub fn with_api_client(mut self, api_client: EventApiClient) -> SimpleEventDispatcher {
+        self.api_client = api_client;
+        self
     }
 }
 
@@ -42,14 +58,22 @@ impl EventDispatcher for SimpleEventDispatcher {
         payload.add_event(event);
 
         // And send
-        match EventApiClient::send(payload) {
+        self.stats.record_enqueued();
+        match self.api_client.send_payload(payload) {
             Ok(_) => {
                 log::info!("Succesfull request to Event API");
+                self.stats.record_success(1);
             }
             Err(report) => {
                 log::error!("Failed request to Event API");
                 log::error!("\n{report:?}");
+                self.stats.record_failure();
+                self.stats.record_dropped(1);
             }
         }
     }
+
+    fn stats(&self) -> Option<DispatcherStats> {
+        Some(self.stats.snapshot())
+    }
 }
@@ -0,0 +1,183 @@
+// External imports
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Snapshot of the counters of an event dispatcher
+///
+/// ```
+/// use optimizely::event_api::{EventDispatcher, SimpleEventDispatcher};
+///
+/// let dispatcher = SimpleEventDispatcher::default();
+///
+/// // Dispatchers that keep track of their events return their counters
+/// let stats = dispatcher.stats().unwrap();
+/// assert_eq!(stats.events_enqueued, 0);
+/// assert_eq!(stats.last_success, None);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DispatcherStats {
+    /// Number of events handed to the dispatcher
+    pub events_enqueued: u64,
+    /// Number of events that were sent to the Event API
+    pub events_sent: u64,
+    /// Number of events that were given up on, because of failed requests, a full queue or a shutdown timeout
+    pub events_dropped: u64,
+    /// Number of successful requests to the Event API
+    pub batches_sent: u64,
+    /// Number of failed requests to the Event API, including requests that were retried
+    pub http_failures: u64,
+    /// Number of requests that were made again after a failure
+    pub retries: u64,
+    /// Number of events that were enqueued, but not yet sent or dropped
+    pub queue_depth: u64,
+    /// Time of the last successful request to the Event API
+    pub last_success: Option<SystemTime>,
+}
+
+#[cfg(feature = "prometheus")]
+impl DispatcherStats {
+    /// Render the counters in the Prometheus text exposition format
+    ///
+    /// ```
+    /// use optimizely::event_api::DispatcherStats;
+    ///
+    /// let stats = DispatcherStats {
+    ///     events_enqueued: 3,
+    ///     ..DispatcherStats::default()
+    /// };
+    ///
+    /// let text = stats.to_prometheus();
+    /// assert!(text.contains("optimizely_events_enqueued_total 3\n"));
+    /// ```
+    pub fn to_prometheus(&self) -> String {
+        let last_success = self
+            .last_success
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0.0, |duration| duration.as_secs_f64());
+
+        let metrics: [(&str, &str, &str, String); 8] = [
+            (
+                "optimizely_events_enqueued_total",
+                "counter",
+                "Events handed to the event dispatcher",
+                self.events_enqueued.to_string(),
+            ),
+            (
+                "optimizely_events_sent_total",
+                "counter",
+                "Events sent to the Event API",
+                self.events_sent.to_string(),
+            ),
+            (
+                "optimizely_events_dropped_total",
+                "counter",
+                "Events that were given up on",
+                self.events_dropped.to_string(),
+            ),
+            (
+                "optimizely_batches_sent_total",
+                "counter",
+                "Successful requests to the Event API",
+                self.batches_sent.to_string(),
+            ),
+            (
+                "optimizely_http_failures_total",
+                "counter",
+                "Failed requests to the Event API",
+                self.http_failures.to_string(),
+            ),
+            (
+                "optimizely_retries_total",
+                "counter",
+                "Requests to the Event API that were retried",
+                self.retries.to_string(),
+            ),
+            ("optimizely_event_queue_depth", "gauge", "Events waiting to be sent", self.queue_depth.to_string()),
+            (
+                "optimizely_last_success_timestamp_seconds",
+                "gauge",
+                "Time of the last successful request to the Event API",
+                last_success.to_string(),
+            ),
+        ];
+
+        let mut text = String::new();
+        for (name, metric_type, help, value) in metrics {
+            text.push_str(&format!("# HELP {name} {help}\n# TYPE {name} {metric_type}\n{name} {value}\n"));
+        }
+        text
+    }
+}
+
+// Counters that are updated by the dispatcher, possibly from multiple threads
+#[derive(Default)]
+pub(crate) struct StatsCounters {
+    events_enqueued: AtomicU64,
+    events_sent: AtomicU64,
+    events_dropped: AtomicU64,
+    batches_sent: AtomicU64,
+    http_failures: AtomicU64,
+    retries: AtomicU64,
+    // Milliseconds since the Unix epoch, zero if there was no success yet
+    last_success: AtomicU64,
+}
+
+impl StatsCounters {
+    pub(crate) fn record_enqueued(&self) {
+        self.events_enqueued.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn record_success(&self, event_count: usize) {
+        self.events_sent
+            .fetch_add(event_count as u64, Ordering::SeqCst);
+        self.batches_sent.fetch_add(1, Ordering::SeqCst);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.last_success
+            .store(now.as_millis() as u64, Ordering::SeqCst);
+    }
+
+    pub(crate) fn record_failure(&self) {
+        self.http_failures.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn record_dropped(&self, event_count: usize) {
+        self.events_dropped
+            .fetch_add(event_count as u64, Ordering::SeqCst);
+    }
+
+    pub(crate) fn events_sent(&self) -> u64 {
+        self.events_sent.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn queue_depth(&self) -> u64 {
+        let handled = self.events_sent.load(Ordering::SeqCst) + self.events_dropped.load(Ordering::SeqCst);
+        self.events_enqueued
+            .load(Ordering::SeqCst)
+            .saturating_sub(handled)
+    }
+
+    pub(crate) fn snapshot(&self) -> DispatcherStats {
+        let last_success = match self.last_success.load(Ordering::SeqCst) {
+            0 => None,
+            millis => Some(UNIX_EPOCH + Duration::from_millis(millis)),
+        };
+
+        DispatcherStats {
+            events_enqueued: self.events_enqueued.load(Ordering::SeqCst),
+            events_sent: self.events_sent.load(Ordering::SeqCst),
+            events_dropped: self.events_dropped.load(Ordering::SeqCst),
+            batches_sent: self.batches_sent.load(Ordering::SeqCst),
+            http_failures: self.http_failures.load(Ordering::SeqCst),
+            retries: self.retries.load(Ordering::SeqCst),
+            queue_depth: self.queue_depth(),
+            last_success,
+        }
+    }
+}
@@ -1,11 +1,15 @@
+// External imports
+use std::time::Duration;
+
 // Imports from super
-use super::Event;
+use super::{CloseReport, DispatcherStats, Event};
 
 /// Trait for sending events to Optimizely Event API
 ///
-/// It is possible to make a custom event disptacher by implementing this trait
+/// It is possible to make a custom event disptacher by implementing this trait.
+/// Since a client can be shared between threads, the event dispatcher has to be `Send` and `Sync`.
 /// ```
-/// use std::cell::RefCell;
+/// use std::sync::Mutex;
 /// use optimizely::event_api::{Event, EventDispatcher};
 /// #
 /// # // Create some example IDs
@@ -21,20 +25,20 @@ use super::Event;
 /// // Struct that will store events instead of sending them
 /// #[derive(Default)]
 /// struct EventStore {
-///     list: RefCell<Vec<Event>>
+///     list: Mutex<Vec<Event>>
 /// }
 ///
 /// // Easy way to get the length of the list inside
 /// impl EventStore {
 ///     fn size(&self) -> usize {
-///         self.list.borrow().len()
+///         self.list.lock().unwrap().len()
 ///     }
 /// }
 ///
 /// // Implementation of the EventDispatcher trait
 /// impl EventDispatcher for EventStore {
 ///     fn send_event(&self, event: Event) {
-///         self.list.borrow_mut().push(event);
+///         self.list.lock().unwrap().push(event);
 ///     }
 /// }
 ///
@@ -46,7 +50,20 @@ use super::Event;
 /// event_store.send_event(event);
 /// assert_eq!(event_store.size(), 1);
 /// ```
-pub trait EventDispatcher {
+pub trait EventDispatcher: Send + Sync {
     /// Send event to destination
     fn send_event(&self, event: Event);
+
+    /// Send any pending events, waiting at most for the given timeout, and stop accepting new events
+    ///
+    /// The default implementation is for dispatchers that send every event right away, so nothing is pending.
+    fn close(&self, timeout: Duration) -> CloseReport {
+        let _ = timeout;
+        CloseReport::default()
+    }
+
+    /// Counters of the events handled by this dispatcher, if the dispatcher keeps track of them
+    fn stats(&self) -> Option<DispatcherStats> {
+        None
+    }
 }
@@ -8,6 +8,11 @@ pub use client::Client;
 pub mod client;
 pub mod datafile;
 pub mod decision;
+pub mod notification;
 
 #[cfg(feature = "online")]
 pub mod event_api;
+
+// Fixtures for the tests
+#[cfg(test)]
+mod test_support;
@@ -0,0 +1,10 @@
+//! Notification listeners for decisions, conversions and datafile updates
+
+// Relative imports of sub modules
+pub use notification_center::{NotificationCenter, NotificationListener};
+pub use payload::{ConfigUpdateNotification, DecisionNotification, Notification, NotificationType};
+#[cfg(feature = "online")]
+pub use payload::{LogEventNotification, TrackNotification};
+
+mod notification_center;
+mod payload;
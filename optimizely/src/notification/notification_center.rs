@@ -0,0 +1,126 @@
+// External imports
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+// Imports from super
+use super::{Notification, NotificationType};
+
+/// Callback that receives notifications of a single type
+pub type NotificationListener = Arc<dyn Fn(&Notification) + Send + Sync>;
+
+/// Registry of notification listeners, available through `Client::notification_center`
+///
+/// ```
+/// use optimizely::Client;
+/// use optimizely::notification::{Notification, NotificationType};
+/// #
+/// # let file_path = "../datafiles/sandbox.json";
+///
+/// // Initialize Optimizely client using local datafile
+/// let optimizely_client = Client::from_local_datafile(file_path)?
+///     .initialize();
+///
+/// // Print every decision that is made
+/// let listener_id = optimizely_client
+///     .notification_center()
+///     .add_listener(NotificationType::Decision, |notification| {
+///         if let Notification::Decision(decision) = notification {
+///             println!("{} => {}", decision.flag_key, decision.variation_key);
+///         }
+///     });
+///
+/// // Make a decision, which calls the listener
+/// let user_context = optimizely_client.create_user_context("123abc789xyz");
+/// let decision = user_context.decide("buy_button");
+///
+/// // Listeners can be removed using the returned ID
+/// assert!(optimizely_client.notification_center().remove_listener(listener_id));
+///
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Default)]
+pub struct NotificationCenter {
+    next_id: AtomicUsize,
+    listeners: RwLock<Vec<(usize, NotificationType, NotificationListener)>>,
+}
+
+impl NotificationCenter {
+    /// Register a listener for the given notification type and return its ID
+    pub fn add_listener<F>(&self, notification_type: NotificationType, listener: F) -> usize
+    where
+        F: Fn(&Notification) + Send + Sync + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        match self.listeners.write() {
+            Ok(mut listeners) => listeners.push((id, notification_type, Arc::new(listener))),
+            Err(_) => log::error!("Notification listeners are poisoned"),
+        }
+
+        id
+    }
+
+    /// Remove the listener with the given ID, returns whether it was registered
+    pub fn remove_listener(&self, listener_id: usize) -> bool {
+        match self.listeners.write() {
+            Ok(mut listeners) => {
+                let count = listeners.len();
+                listeners.retain(|(id, _, _)| *id != listener_id);
+                listeners.len() != count
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Remove all listeners of the given notification type
+    pub fn clear_listeners(&self, notification_type: NotificationType) {
+        if let Ok(mut listeners) = self.listeners.write() {
+            listeners.retain(|(_, listener_type, _)| *listener_type != notification_type);
+        }
+    }
+
+    /// Remove all listeners
+    pub fn clear_all_listeners(&self) {
+        if let Ok(mut listeners) = self.listeners.write() {
+            listeners.clear();
+        }
+    }
+
+    /// Whether any listener is registered for the given notification type
+    ///
+    /// Used to avoid building payloads that nobody will receive.
+    pub(crate) fn has_listeners(&self, notification_type: NotificationType) -> bool {
+        match self.listeners.read() {
+            Ok(listeners) => listeners
+                .iter()
+                .any(|(_, listener_type, _)| *listener_type == notification_type),
+            Err(_) => false,
+        }
+    }
+
+    /// Call every listener that is registered for the type of the notification
+    pub(crate) fn send(&self, notification: &Notification) {
+        let notification_type = notification.notification_type();
+
+        // Copy the listeners first, so a listener is able to add or remove listeners itself
+        let listeners = match self.listeners.read() {
+            Ok(listeners) => listeners
+                .iter()
+                .filter(|(_, listener_type, _)| *listener_type == notification_type)
+                .map(|(_, _, listener)| Arc::clone(listener))
+                .collect::<Vec<_>>(),
+            Err(_) => {
+                log::error!("Notification listeners are poisoned");
+                return;
+            }
+        };
+
+        for listener in listeners {
+            // A panicking listener should not take down the decision or the other listeners
+            if catch_unwind(AssertUnwindSafe(|| listener(notification))).is_err() {
+                log::error!("Notification listener for {notification_type:?} panicked");
+            }
+        }
+    }
+}
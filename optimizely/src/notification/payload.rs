@@ -0,0 +1,104 @@
+// External imports
+use serde_json::{Map, Value};
+
+// Imports from crate
+use crate::client::UserAttributes;
+use crate::datafile::DatafileDiff;
+#[cfg(feature = "online")]
+use crate::event_api::Event;
+
+/// The kinds of notifications a listener can be registered for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NotificationType {
+    /// A decision was made for a feature flag (DECIDE)
+    Decision,
+    /// A conversion event was tracked (TRACK)
+    #[cfg(feature = "online")]
+    Track,
+    /// The datafile of the client was replaced (OPTIMIZELY_CONFIG_UPDATE)
+    OptimizelyConfigUpdate,
+    /// An event was handed to the event dispatcher (LOG_EVENT)
+    #[cfg(feature = "online")]
+    LogEvent,
+}
+
+/// Payload that is passed to the notification listeners
+#[derive(Debug)]
+pub enum Notification<'a> {
+    /// Payload for `NotificationType::Decision`
+    Decision(DecisionNotification<'a>),
+    /// Payload for `NotificationType::Track`
+    #[cfg(feature = "online")]
+    Track(TrackNotification<'a>),
+    /// Payload for `NotificationType::OptimizelyConfigUpdate`
+    OptimizelyConfigUpdate(ConfigUpdateNotification<'a>),
+    /// Payload for `NotificationType::LogEvent`
+    #[cfg(feature = "online")]
+    LogEvent(LogEventNotification<'a>),
+}
+
+impl Notification<'_> {
+    /// Get the type of this notification
+    pub fn notification_type(&self) -> NotificationType {
+        match self {
+            Notification::Decision(_) => NotificationType::Decision,
+            #[cfg(feature = "online")]
+            Notification::Track(_) => NotificationType::Track,
+            Notification::OptimizelyConfigUpdate(_) => NotificationType::OptimizelyConfigUpdate,
+            #[cfg(feature = "online")]
+            Notification::LogEvent(_) => NotificationType::LogEvent,
+        }
+    }
+}
+
+/// Details of a decision made for a feature flag
+#[derive(Debug)]
+pub struct DecisionNotification<'a> {
+    /// ID of the user the decision was made for
+    pub user_id: &'a str,
+    /// Attributes of the user at the time of the decision
+    pub attributes: &'a UserAttributes,
+    /// Key of the feature flag
+    pub flag_key: &'a str,
+    /// Whether the flag is enabled for the user
+    pub enabled: bool,
+    /// Key of the decided variation, "off" when the user did not qualify for any rule
+    pub variation_key: &'a str,
+    /// Key of the experiment or rollout rule that was used, if any
+    pub rule_key: Option<&'a str>,
+    /// Values of the flag variables, empty when the `exclude_variables` option was set
+    pub variables: &'a Map<String, Value>,
+    /// Whether a decision event was sent to the event dispatcher
+    pub decision_event_dispatched: bool,
+}
+
+/// Details of a tracked conversion event
+#[cfg(feature = "online")]
+#[derive(Debug)]
+pub struct TrackNotification<'a> {
+    /// ID of the user that converted
+    pub user_id: &'a str,
+    /// Attributes of the user at the time of the conversion
+    pub attributes: &'a UserAttributes,
+    /// Key of the conversion event
+    pub event_key: &'a str,
+}
+
+/// Details of a datafile update
+#[derive(Debug)]
+pub struct ConfigUpdateNotification<'a> {
+    /// Revision of the previous datafile
+    pub old_revision: u32,
+    /// Revision of the new datafile
+    pub new_revision: u32,
+    /// What changed between the previous and the new datafile
+    pub diff: &'a DatafileDiff,
+}
+
+/// Details of an event that is handed to the event dispatcher
+#[cfg(feature = "online")]
+#[derive(Debug)]
+pub struct LogEventNotification<'a> {
+    /// The decision or conversion event
+    pub event: &'a Event,
+}
@@ -0,0 +1,89 @@
+//! Fixtures shared by the unit tests and, through `tests/common`, the integration tests
+//!
+//! Only uses the standard library, so the same file compiles as part of either crate.
+
+// Not every crate that includes this file uses every fixture
+#![allow(dead_code)]
+
+// External imports
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::thread;
+
+/// Request as received by a local stand-in server
+pub struct Request {
+    pub headers: Vec<String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Value of the header with the given name, which is not case sensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find_map(|header| {
+            let (header_name, value) = header.split_once(": ")?;
+            header_name.eq_ignore_ascii_case(name).then_some(value)
+        })
+    }
+}
+
+/// Local stand-in for the CDN or the Event API, which answers every request with the status and body of `respond`
+///
+/// Returns the base URL of the server, like `http://127.0.0.1:54321`.
+pub fn serve<F>(mut respond: F) -> String
+where
+    F: FnMut(Request) -> (&'static str, String) + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(&stream);
+
+            // Read the headers, which end with an empty line
+            let mut headers = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                headers.push(line.to_owned());
+            }
+
+            let mut request = Request {
+                headers,
+                body: Vec::new(),
+            };
+            let content_length = request
+                .header("content-length")
+                .map_or(0, |value| value.parse().unwrap());
+            request.body.resize(content_length, 0);
+            reader.read_exact(&mut request.body).unwrap();
+
+            let (status, body) = respond(request);
+            let response =
+                format!("HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}", body.len());
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    url
+}
+
+/// Base URL on which nothing listens, since the listener on its port is already dropped, so every request fails
+pub fn unreachable_url() -> String {
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    format!("http://{address}")
+}
+
+/// Path of a JSON file in the temporary directory, unique for each test
+pub fn temp_path(kind: &str, name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("optimizely-{kind}-{}-{name}.json", std::process::id()))
+}
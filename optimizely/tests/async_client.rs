@@ -0,0 +1,163 @@
+#![cfg(feature = "async")]
+
+// External imports
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Imports from Optimizely crate
+use optimizely::event_api::{
+    AsyncBatchedEventDispatcher, AsyncEventDispatcher, CloseFuture, Event, EventApiClient, OverflowPolicy,
+};
+use optimizely::Client;
+
+// Relative imports of sub modules
+use common::test_support::unreachable_url;
+use common::{ACCOUNT_ID, FILE_PATH};
+mod common;
+
+// Async dispatcher that only moves events to the sent list when it is closed
+#[derive(Clone, Default)]
+struct DelayedEventStore {
+    queued: Arc<Mutex<Vec<Event>>>,
+    sent: Arc<Mutex<Vec<Event>>>,
+}
+
+impl AsyncEventDispatcher for DelayedEventStore {
+    fn send_event(&self, event: Event) {
+        self.queued.lock().unwrap().push(event);
+    }
+
+    fn close(&self) -> CloseFuture<'_> {
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            let events = std::mem::take(&mut *self.queued.lock().unwrap());
+            self.sent.lock().unwrap().extend(events);
+        })
+    }
+}
+
+#[tokio::test]
+async fn close_flushes_async_event_dispatcher() {
+    let event_store = DelayedEventStore::default();
+    let client = Client::from_local_datafile(FILE_PATH)
+        .unwrap()
+        .with_async_event_dispatcher(event_store.clone())
+        .initialize();
+
+    // Decisions are made synchronously and hand over their events
+    let decision = client.create_user_context("user1").decide("buy_button");
+    assert_eq!(decision.variation_key(), "danger");
+    assert_eq!(event_store.queued.lock().unwrap().len(), 1);
+    assert!(event_store.sent.lock().unwrap().is_empty());
+
+    // Closing waits until the dispatcher is done
+    client.close_async().await;
+    assert!(event_store.queued.lock().unwrap().is_empty());
+    assert_eq!(event_store.sent.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn close_without_events() {
+    let client = Client::from_local_datafile(FILE_PATH)
+        .unwrap()
+        .with_async_event_dispatcher(AsyncBatchedEventDispatcher::new())
+        .with_async_polling(Duration::from_secs(1))
+        .initialize();
+
+    // Polling is ignored for a local datafile, and an empty dispatcher closes right away
+    tokio::time::timeout(Duration::from_secs(1), client.close_async())
+        .await
+        .expect("close should not hang");
+
+    // Events after closing are dropped instead of panicking
+    let options = optimizely::decision::DecideOptions::default();
+    client
+        .create_user_context("user1")
+        .decide_with_options("buy_button", &options);
+}
+
+// Dispatcher with a queue of two events that keeps the user ids of the events passed to its drop listener
+fn async_dispatcher_with_dropped_users(
+    policy: OverflowPolicy,
+) -> (AsyncBatchedEventDispatcher, Arc<Mutex<Vec<String>>>) {
+    // Every request fails
+    let api_client = EventApiClient::default().with_endpoint_url(format!("{}/v1/events", unreachable_url()));
+
+    let dropped_users = Arc::new(Mutex::new(Vec::new()));
+    let listener_users = Arc::clone(&dropped_users);
+    let dispatcher = AsyncBatchedEventDispatcher::with_queue(2, policy, Duration::from_secs(30))
+        .with_api_client(api_client)
+        .with_drop_listener(move |event| {
+            listener_users
+                .lock()
+                .unwrap()
+                .push(event.user_id().to_owned());
+        });
+
+    (dispatcher, dropped_users)
+}
+
+// Send conversion events for the given users, without giving the task of the dispatcher a chance to run
+fn send_events(dispatcher: &AsyncBatchedEventDispatcher, user_ids: &[&str]) {
+    for user_id in user_ids {
+        dispatcher.send_event(Event::conversion(ACCOUNT_ID, user_id, "22305150298", "purchase"));
+    }
+}
+
+#[tokio::test]
+async fn full_queue_drops_newest_events() {
+    let (dispatcher, dropped_users) = async_dispatcher_with_dropped_users(OverflowPolicy::DropNewest);
+    send_events(&dispatcher, &["user0", "user1", "user2", "user3"]);
+
+    assert_eq!(*dropped_users.lock().unwrap(), vec!["user2", "user3"]);
+
+    let stats = dispatcher.stats().unwrap();
+    assert_eq!(stats.events_enqueued, 4);
+    assert_eq!(stats.events_dropped, 2);
+    assert_eq!(stats.queue_depth, 2);
+}
+
+#[tokio::test]
+async fn full_queue_drops_oldest_events() {
+    let (dispatcher, dropped_users) = async_dispatcher_with_dropped_users(OverflowPolicy::DropOldest);
+    send_events(&dispatcher, &["user0", "user1", "user2", "user3"]);
+
+    assert_eq!(*dropped_users.lock().unwrap(), vec!["user0", "user1"]);
+    assert_eq!(dispatcher.stats().unwrap().events_dropped, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn full_queue_blocks_on_multi_threaded_runtime() {
+    let (dispatcher, dropped_users) = async_dispatcher_with_dropped_users(OverflowPolicy::Block);
+
+    // Every event is kept, while the task moves the queued events into its batch
+    send_events(&dispatcher, &["user0", "user1", "user2", "user3"]);
+    assert!(dropped_users.lock().unwrap().is_empty());
+
+    // The events of the failed request are reported when closing
+    dispatcher.close().await;
+    assert_eq!(*dropped_users.lock().unwrap(), vec!["user0", "user1", "user2", "user3"]);
+    let stats = dispatcher.stats().unwrap();
+    assert_eq!((stats.events_enqueued, stats.events_dropped, stats.queue_depth), (4, 4, 0));
+}
+
+#[tokio::test]
+async fn full_queue_does_not_block_on_single_threaded_runtime() {
+    let (dispatcher, dropped_users) = async_dispatcher_with_dropped_users(OverflowPolicy::Block);
+
+    // The task can not empty the queue while waiting, so the event is dropped instead
+    send_events(&dispatcher, &["user0", "user1", "user2"]);
+    assert_eq!(*dropped_users.lock().unwrap(), vec!["user2"]);
+}
+
+#[tokio::test]
+async fn events_after_close_are_dropped() {
+    let (dispatcher, dropped_users) = async_dispatcher_with_dropped_users(OverflowPolicy::DropNewest);
+    dispatcher.close().await;
+
+    send_events(&dispatcher, &["user1", "user2"]);
+
+    assert_eq!(*dropped_users.lock().unwrap(), vec!["user1", "user2"]);
+    let stats = dispatcher.stats().unwrap();
+    assert_eq!((stats.events_enqueued, stats.events_dropped, stats.queue_depth), (2, 2, 0));
+}
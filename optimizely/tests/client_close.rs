@@ -0,0 +1,54 @@
+// External imports
+use std::time::Duration;
+
+// Imports from Optimizely crate
+use optimizely::event_api::{BatchedEventDispatcher, CloseReport};
+use optimizely::Client;
+
+// Relative imports of sub modules
+use common::{setup, FILE_PATH};
+mod common;
+
+#[test]
+fn close_with_immediate_dispatcher() {
+    let ctx = setup();
+    ctx.client.create_user_context("user1").decide("buy_button");
+
+    // Events of the test dispatcher are stored right away, so nothing is pending
+    assert_eq!(ctx.client.close(Duration::from_secs(1)), CloseReport::default());
+    assert_eq!(ctx.event_list.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn close_with_batched_dispatcher() {
+    let client = Client::from_local_datafile(FILE_PATH)
+        .unwrap()
+        .with_event_dispatcher(BatchedEventDispatcher::default())
+        .initialize();
+
+    for user_id in ["user1", "user2", "user3"] {
+        client.create_user_context(user_id).decide("buy_button");
+    }
+
+    // Every pending event is either sent or dropped, even if the timeout expires right away
+    let report = client.close(Duration::ZERO);
+    assert_eq!(report.sent + report.dropped, 3);
+
+    // Closing again has nothing left to do, and later events are ignored
+    assert_eq!(client.close(Duration::from_secs(1)), CloseReport::default());
+    client.create_user_context("user4").decide("buy_button");
+}
+
+#[test]
+fn close_waits_for_last_batch() {
+    let client = Client::from_local_datafile(FILE_PATH)
+        .unwrap()
+        .with_event_dispatcher(BatchedEventDispatcher::default())
+        .initialize();
+
+    client.create_user_context("user1").decide("buy_button");
+
+    // With enough time the last batch is attempted, so it is sent or dropped because the request failed
+    let report = client.close(Duration::from_secs(30));
+    assert_eq!(report.sent + report.dropped, 1);
+}
@@ -54,12 +54,9 @@ fn with_missing_properties() {
         "Report did not include ClientError::InvalidDatafile"
     );
 
-    // Verify the datafile error type
+    // Verify the datafile error type, the version is checked first
     let datafile_error = report.downcast_ref::<DatafileError>().unwrap();
-    assert!(
-        matches!(datafile_error, DatafileError::InvalidJson),
-        "Report did not include DatafileError::KeyNotFound"
-    );
+    assert_eq!(datafile_error, &DatafileError::MissingField(String::from("version")));
 }
 
 #[test]
@@ -68,6 +65,7 @@ fn with_invalid_array_properties() {
     let json = r#"
     {
         "accountId": "21537940595",
+        "version": "4",
         "revision": "73",
         "rollouts": null,
         "experiments": null,
@@ -76,7 +74,7 @@ fn with_invalid_array_properties() {
     }"#;
 
     // Get error report
-    let report = Client::from_string(json.into()).err().unwrap();
+    let report = Client::from_string(json).err().unwrap();
 
     // Verify the client error type
     let client_error = report.downcast_ref::<ClientError>().unwrap();
@@ -85,12 +83,9 @@ fn with_invalid_array_properties() {
         "Report did not include ClientError::InvalidDatafile"
     );
 
-    // Verify the datafile error type
+    // Verify the datafile error type, which points to the first invalid property
     let datafile_error = report.downcast_ref::<DatafileError>().unwrap();
-    assert!(
-        matches!(datafile_error, DatafileError::InvalidJson),
-        "Report did not include DatafileError::InvalidType"
-    );
+    assert_eq!(datafile_error, &DatafileError::InvalidType(String::from("rollouts")));
 }
 
 #[test]
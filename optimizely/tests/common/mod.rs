@@ -2,13 +2,17 @@
 #![allow(dead_code)]
 
 // External imports
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
 // Imports from Optimizely crate
+use optimizely::client::UninitializedClient;
 use optimizely::event_api::{Event, EventDispatcher};
 use optimizely::Client;
 
+// Fixtures that are shared with the unit tests
+#[path = "../../src/test_support.rs"]
+pub mod test_support;
+
 // This is the account ID of mark.biesheuvel@optimizely.com
 pub const ACCOUNT_ID: &str = "21537940595";
 
@@ -22,26 +26,26 @@ pub const FILE_PATH: &str = "../datafiles/sandbox.json";
 // This is the revision number of the bundled datafile
 pub const REVISION: u32 = 73;
 
-// List of Events wrapped in a reference counted mutable memory location
-type EventList = Rc<RefCell<Vec<Event>>>;
+// List of Events wrapped in a thread-safe reference counted mutable memory location
+type EventList = Arc<Mutex<Vec<Event>>>;
 
 // Struct that holds the EventList and implement the EventDispatcher trait
 #[derive(Default)]
 pub(super) struct EventStore {
-    list: Rc<RefCell<Vec<Event>>>,
+    list: EventList,
 }
 
 // Return a new reference counted point to the list
 impl EventStore {
-    fn list(&self) -> Rc<RefCell<Vec<Event>>> {
-        Rc::clone(&self.list)
+    fn list(&self) -> EventList {
+        Arc::clone(&self.list)
     }
 }
 
 // Implementing the EventDispatcher using the interior mutability pattern
 impl EventDispatcher for EventStore {
     fn send_event(&self, event: Event) {
-        self.list.borrow_mut().push(event);
+        self.list.lock().unwrap().push(event);
     }
 }
 
@@ -67,3 +71,44 @@ pub(super) fn setup() -> TestContext {
 
     TestContext { client, event_list }
 }
+
+// A setup function for tests that need to modify the datafile first
+pub(super) fn setup_with_datafile<F>(modify: F) -> TestContext
+where
+    F: FnOnce(&mut serde_json::Value),
+{
+    // Parse the bundled datafile and apply the modification
+    let content = std::fs::read_to_string(FILE_PATH).expect("local datafile should exist");
+    let mut datafile = serde_json::from_str(&content).expect("local datafile should be valid JSON");
+    modify(&mut datafile);
+
+    // Create a struct to store events
+    let event_store = EventStore::default();
+    let event_list = event_store.list();
+
+    // Build client
+    let client = Client::from_string(&datafile.to_string())
+        .expect("modified datafile should work")
+        .with_event_dispatcher(event_store)
+        .initialize();
+
+    TestContext { client, event_list }
+}
+
+// A setup function for tests that need to configure the client further
+pub(super) fn setup_with_client<F>(configure: F) -> TestContext
+where
+    F: FnOnce(UninitializedClient) -> UninitializedClient,
+{
+    // Create a struct to store events
+    let event_store = EventStore::default();
+    let event_list = event_store.list();
+
+    // Build client
+    let client = Client::from_local_datafile(FILE_PATH).expect("local datafile should work");
+    let client = configure(client)
+        .with_event_dispatcher(event_store)
+        .initialize();
+
+    TestContext { client, event_list }
+}
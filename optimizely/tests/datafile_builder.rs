@@ -0,0 +1,137 @@
+// External imports
+use serde_json::json;
+
+// Imports from Optimizely crate
+use optimizely::datafile::lint::{self, LintRule};
+use optimizely::datafile::{Datafile, DatafileBuilder, FlagBuilder, RuleBuilder, VariationBuilder};
+use optimizely::decision::DecideOptions;
+use optimizely::{user_attributes, Client};
+
+fn checkout_builder() -> DatafileBuilder {
+    DatafileBuilder::new()
+        .account_id("21537940595")
+        .revision(7)
+        .attribute("platform")
+        .audience(
+            "web",
+            json!(["or", {"type": "custom_attribute", "match": "exact", "name": "platform", "value": "web"}]),
+        )
+        .event("purchase", ["checkout_experiment"])
+        .flag(
+            FlagBuilder::new("checkout")
+                .variable("button_color", "string", "blue")
+                .experiment(
+                    RuleBuilder::new("checkout_experiment")
+                        .audience("web")
+                        .variation("control", 5_000)
+                        .with_variation(VariationBuilder::new("treatment", 5_000).variable("button_color", "green")),
+                )
+                .rollout_rule(RuleBuilder::new("everyone_else").variation("on", 10_000)),
+        )
+        .flag(FlagBuilder::new("dark_mode").rollout_rule(RuleBuilder::new("nobody").variation("off", 10_000)))
+}
+
+#[test]
+fn generated_ids_are_consistent() {
+    let json = checkout_builder().to_json();
+
+    let audience_id = &json["typedAudiences"][0]["id"];
+    let experiment = &json["experiments"][0];
+    assert_eq!(experiment["audienceIds"], json!([audience_id]));
+    assert_eq!(experiment["audienceConditions"], json!(["or", audience_id]));
+    assert_eq!(json["events"][0]["experimentIds"], json!([experiment["id"]]));
+
+    // Traffic is allocated cumulatively to the generated variation ids
+    assert_eq!(experiment["trafficAllocation"][0]["entityId"], experiment["variations"][0]["id"]);
+    assert_eq!(experiment["trafficAllocation"][1]["entityId"], experiment["variations"][1]["id"]);
+    assert_eq!(experiment["trafficAllocation"][1]["endOfRange"], 10_000);
+
+    let flag = &json["featureFlags"][0];
+    assert_eq!(flag["experimentIds"], json!([experiment["id"]]));
+    assert_eq!(flag["rolloutId"], json["rollouts"][0]["id"]);
+    assert_eq!(experiment["variations"][1]["variables"][0]["id"], flag["variables"][0]["id"]);
+
+    // Generating twice gives the same document
+    assert_eq!(checkout_builder().to_json(), json);
+}
+
+#[test]
+fn built_datafile_makes_decisions() {
+    let datafile = checkout_builder().build().unwrap();
+    assert_eq!(datafile.revision(), 7);
+    assert_eq!(datafile.account_id(), "21537940595");
+
+    let json = checkout_builder().to_json().to_string();
+    let client = Client::from_string(&json).unwrap().initialize();
+    let decide_options = DecideOptions {
+        disable_decision_event: true,
+        ..DecideOptions::default()
+    };
+
+    let mut variation_keys = Vec::new();
+    for index in 0..100 {
+        let user_id = format!("user{index}");
+
+        // Web users are in the experiment, with both variations in use
+        let user_context =
+            client.create_user_context_with_attributes(&user_id, user_attributes! { "platform" => "web" });
+        let decision = user_context.decide_with_options("checkout", &decide_options);
+        assert_eq!(decision.rule_key(), Some("checkout_experiment"));
+        let expected_color = match decision.variation_key() {
+            "control" => "blue",
+            "treatment" => "green",
+            variation_key => panic!("Unexpected variation {variation_key}"),
+        };
+        assert_eq!(decision.variables()["button_color"], json!(expected_color));
+        variation_keys.push(decision.variation_key().to_owned());
+
+        // Other users fall through to the rollout
+        let user_context =
+            client.create_user_context_with_attributes(&user_id, user_attributes! { "platform" => "ios" });
+        let decision = user_context.decide_with_options("checkout", &decide_options);
+        assert_eq!(decision.variation_key(), "on");
+        assert!(decision.enabled());
+
+        // The variation "off" disables the feature
+        let decision = user_context.decide_with_options("dark_mode", &decide_options);
+        assert_eq!(decision.variation_key(), "off");
+        assert!(!decision.enabled());
+    }
+    assert!(variation_keys.iter().any(|key| key == "control"));
+    assert!(variation_keys.iter().any(|key| key == "treatment"));
+}
+
+#[test]
+fn round_trip_through_serialization() {
+    let datafile = checkout_builder().build().unwrap();
+    let json = serde_json::to_string(&datafile).unwrap();
+
+    assert!(Datafile::build(&json).is_ok());
+}
+
+#[test]
+fn unknown_names_are_dangling_references() {
+    let datafile = DatafileBuilder::new()
+        .event("purchase", ["missing_experiment"])
+        .flag(
+            FlagBuilder::new("checkout")
+                .experiment(RuleBuilder::new("checkout_experiment").audience("missing_audience")),
+        )
+        .build()
+        .unwrap();
+
+    // Neither reference breaks a decision, so they are only reported by the linter
+    let messages = lint::check(&datafile)
+        .into_iter()
+        .filter(|warning| warning.rule() == LintRule::DanglingReference)
+        .map(|warning| warning.message().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "refers to experiment `missing_experiment`, which does not exist",
+            "refers to audience `missing_audience`, which does not exist",
+            "refers to audience `missing_audience`, which does not exist",
+        ]
+    );
+}
@@ -0,0 +1,111 @@
+// External imports
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+// Imports from Optimizely crate
+use optimizely::datafile::{Changes, Datafile};
+use optimizely::notification::{Notification, NotificationType};
+
+// Relative imports of sub modules
+use common::{setup_with_datafile, FILE_PATH};
+mod common;
+
+fn bundled_datafile() -> Datafile {
+    let content = std::fs::read_to_string(FILE_PATH).unwrap();
+    Datafile::build(&content).unwrap()
+}
+
+// Build the bundled datafile after applying a modification
+fn build_with<F>(modify: F) -> Datafile
+where
+    F: FnOnce(&mut Value),
+{
+    let content = std::fs::read_to_string(FILE_PATH).unwrap();
+    let mut datafile: Value = serde_json::from_str(&content).unwrap();
+    modify(&mut datafile);
+
+    Datafile::build(&datafile.to_string()).unwrap()
+}
+
+fn changes(added: &[&str], removed: &[&str], modified: &[&str]) -> Changes {
+    let to_strings = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect();
+    Changes {
+        added: to_strings(added),
+        removed: to_strings(removed),
+        modified: to_strings(modified),
+    }
+}
+
+#[test]
+fn same_configuration() {
+    let old = bundled_datafile();
+    let new = build_with(|datafile| datafile["revision"] = json!("74"));
+
+    assert!(old.diff(&new).is_empty());
+}
+
+#[test]
+fn changed_configuration() {
+    let old = bundled_datafile();
+    let new = build_with(|datafile| {
+        datafile["featureFlags"].as_array_mut().unwrap().remove(5);
+        datafile["featureFlags"][4]["variables"][0]["defaultValue"] = json!("Welcome");
+        datafile["experiments"][0]["trafficAllocation"][0]["endOfRange"] = json!(2000);
+        datafile["typedAudiences"][1]["name"] = json!("optimizely team");
+    });
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.flags, changes(&[], &["simplified_checkout"], &["header_text"]));
+    assert_eq!(diff.experiments, changes(&[], &[], &["buy_button_experiment"]));
+    assert_eq!(diff.rollout_rules, changes(&[], &["simplified_checkout.default-rollout-19333-21533480907"], &[]));
+    assert_eq!(diff.audiences, changes(&["optimizely team"], &["optimizely"], &[]));
+    assert_eq!(diff.variables, changes(&[], &[], &["header_text.text"]));
+    assert_eq!(
+        diff.traffic_allocations,
+        changes(&[], &["simplified_checkout.default-rollout-19333-21533480907"], &["buy_button_experiment"])
+    );
+
+    // Going back reverses the diff
+    let reverse = new.diff(&old);
+    assert_eq!(reverse.audiences, changes(&["optimizely"], &["optimizely team"], &[]));
+    assert_eq!(reverse.flags, changes(&["simplified_checkout"], &[], &["header_text"]));
+}
+
+#[test]
+fn serialized_diff() {
+    let old = bundled_datafile();
+    let new = build_with(|datafile| datafile["experiments"][0]["trafficAllocation"][0]["endOfRange"] = json!(2000));
+
+    let json = serde_json::to_value(old.diff(&new)).unwrap();
+    assert_eq!(
+        json["trafficAllocations"],
+        json!({"added": [], "removed": [], "modified": ["buy_button_experiment"]})
+    );
+    assert_eq!(json["flags"], json!({"added": [], "removed": [], "modified": []}));
+}
+
+#[test]
+fn config_update_notification() {
+    let ctx = setup_with_datafile(|datafile| {
+        datafile["revision"] = json!("80");
+        datafile["featureFlags"][4]["variables"][0]["defaultValue"] = json!("Welcome");
+    });
+
+    let diffs = Arc::new(Mutex::new(Vec::new()));
+    let listener_diffs = Arc::clone(&diffs);
+    ctx.client
+        .notification_center()
+        .add_listener(NotificationType::OptimizelyConfigUpdate, move |notification| {
+            if let Notification::OptimizelyConfigUpdate(update) = notification {
+                listener_diffs.lock().unwrap().push(update.diff.clone());
+            }
+        });
+
+    assert!(ctx.client.update_datafile(bundled_datafile()));
+
+    let diffs = diffs.lock().unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].variables, changes(&[], &[], &["header_text.text"]));
+    assert_eq!(diffs[0].flags, changes(&[], &[], &["header_text"]));
+    assert!(diffs[0].experiments.is_empty());
+}
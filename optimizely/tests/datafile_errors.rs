@@ -0,0 +1,168 @@
+// External imports
+use error_stack::Report;
+use serde_json::{json, Value};
+
+// Imports from Optimizely crate
+use optimizely::datafile::{Datafile, DatafileError};
+use optimizely::decision::RuleType;
+
+// Relative imports of sub modules
+use common::FILE_PATH;
+mod common;
+
+// Build the bundled datafile after applying a modification, which should fail
+fn build_with<F>(modify: F) -> Report<DatafileError>
+where
+    F: FnOnce(&mut Value),
+{
+    let content = std::fs::read_to_string(FILE_PATH).unwrap();
+    let mut datafile: Value = serde_json::from_str(&content).unwrap();
+    modify(&mut datafile);
+
+    Datafile::build(&datafile.to_string()).err().unwrap()
+}
+
+#[test]
+fn invalid_json() {
+    let report = Datafile::build(r#"{"version": "4",}"#).err().unwrap();
+    assert_eq!(report.current_context(), &DatafileError::InvalidJson);
+
+    // Trailing content after the document is not allowed either
+    let content = std::fs::read_to_string(FILE_PATH).unwrap() + "}";
+    let report = Datafile::build(&content).err().unwrap();
+    assert_eq!(report.current_context(), &DatafileError::InvalidJson);
+}
+
+#[test]
+fn unsupported_version() {
+    let report = build_with(|datafile| datafile["version"] = json!("1"));
+    assert_eq!(report.current_context(), &DatafileError::UnsupportedVersion(String::from("1")));
+}
+
+#[test]
+fn missing_field() {
+    let report = build_with(|datafile| {
+        datafile["experiments"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("layerId");
+    });
+    assert_eq!(report.current_context(), &DatafileError::MissingField(String::from("experiments[0].layerId")));
+
+    let report = build_with(|datafile| {
+        datafile.as_object_mut().unwrap().remove("revision");
+    });
+    assert_eq!(report.current_context(), &DatafileError::MissingField(String::from("revision")));
+}
+
+#[test]
+fn invalid_type() {
+    let report =
+        build_with(|datafile| datafile["experiments"][0]["trafficAllocation"][1]["endOfRange"] = json!("5000"));
+    assert_eq!(
+        report.current_context(),
+        &DatafileError::InvalidType(String::from("experiments[0].trafficAllocation[1].endOfRange"))
+    );
+
+    // The revision is a string, that should contain a number
+    let report = build_with(|datafile| datafile["revision"] = json!("latest"));
+    assert_eq!(report.current_context(), &DatafileError::InvalidType(String::from("revision")));
+
+    // The underlying message is kept in the report
+    assert!(format!("{report:?}").contains("invalid digit"));
+}
+
+#[test]
+fn invalid_condition() {
+    let report = build_with(|datafile| datafile["typedAudiences"][0]["conditions"][0] = json!("xor"));
+    assert_eq!(
+        report.current_context(),
+        &DatafileError::InvalidCondition(String::from("typedAudiences[0].conditions"))
+    );
+
+    let report = build_with(|datafile| datafile["typedAudiences"][0]["conditions"] = json!(42));
+    assert_eq!(
+        report.current_context(),
+        &DatafileError::InvalidCondition(String::from("typedAudiences[0].conditions"))
+    );
+
+    // Only unsupported match types are kept as they are, supported ones need all of their fields
+    let report = build_with(|datafile| {
+        datafile["typedAudiences"][0]["conditions"] = json!(["or", {"type": "custom_attribute", "match": "exact"}]);
+    });
+    assert_eq!(
+        report.current_context(),
+        &DatafileError::InvalidCondition(String::from("typedAudiences[0].conditions"))
+    );
+}
+
+#[test]
+fn invalid_references() {
+    let report = build_with(|datafile| {
+        datafile["featureFlags"][1]["rolloutId"] = json!("rollout-missing");
+        datafile["featureFlags"][1]["experimentIds"] = json!(["9300000127039", "9300000000000"]);
+        datafile["experiments"][0]["trafficAllocation"][0]["entityId"] = json!("12345");
+        datafile["experiments"][0]["audienceConditions"] = json!(["or", "18423300742", "404"]);
+        datafile["rollouts"][0]["experiments"][0]["audienceIds"] = json!(["405"]);
+        datafile["events"][0]["experimentIds"] = json!(["9300000000001"]);
+    });
+
+    // Every reference that breaks decisions is reported, sorted by path, but missing experiments and audiences are not
+    let invalid_references = match report.current_context() {
+        DatafileError::InvalidReferences(invalid_references) => invalid_references,
+        error => panic!("Unexpected error {error:?}"),
+    };
+    let summary = invalid_references
+        .iter()
+        .map(|reference| (reference.path(), reference.entity(), reference.id()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            ("experiments[\"buy_button_experiment\"].trafficAllocation", "variation", "12345"),
+            ("featureFlags[\"buy_button\"].rolloutId", "rollout", "rollout-missing"),
+        ]
+    );
+
+    // Each violation is readable from the logged report
+    let message = format!("{report:?}");
+    assert!(message.contains(&invalid_references[1].to_string()));
+    assert_eq!(
+        invalid_references[1].to_string(),
+        "featureFlags[\"buy_button\"].rolloutId refers to rollout `rollout-missing`, which does not exist"
+    );
+}
+
+#[test]
+fn experiments_of_groups() {
+    // Move the experiment of a flag into a mutual exclusion group, which is not parsed
+    let content = std::fs::read_to_string(FILE_PATH).unwrap();
+    let mut datafile: Value = serde_json::from_str(&content).unwrap();
+    let experiment = datafile["experiments"].as_array_mut().unwrap().remove(0);
+    datafile["groups"] = json!([{
+        "id": "19228",
+        "policy": "random",
+        "trafficAllocation": [{"entityId": experiment["id"], "endOfRange": 10000}],
+        "experiments": [experiment],
+    }]);
+
+    // The datafile is accepted, and the flag only uses its rollout
+    let datafile = Datafile::build(&datafile.to_string()).unwrap();
+    let flag = datafile.flag("buy_button").unwrap();
+    assert_eq!(flag.experiments_ids(), &["9300000127039"]);
+    assert!(!flag.rules().is_empty());
+    assert!(flag
+        .rules()
+        .iter()
+        .all(|rule| rule.rule_type() == RuleType::Rollout));
+}
+
+#[test]
+fn flag_without_rollout() {
+    // Flags without a rollout have an empty rollout id, which is not a reference
+    let content = std::fs::read_to_string(FILE_PATH).unwrap();
+    let mut datafile: Value = serde_json::from_str(&content).unwrap();
+    datafile["featureFlags"][1]["rolloutId"] = json!("");
+
+    assert!(Datafile::build(&datafile.to_string()).is_ok());
+}
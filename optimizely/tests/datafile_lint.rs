@@ -0,0 +1,212 @@
+// External imports
+use serde_json::json;
+
+// Imports from Optimizely crate
+use optimizely::datafile::lint::{self, LintRule, LintWarning, Severity};
+use optimizely::datafile::{Datafile, DatafileBuilder, FlagBuilder, RuleBuilder, VariationBuilder};
+
+// Relative imports of sub modules
+use common::FILE_PATH;
+mod common;
+
+fn summary(warnings: &[LintWarning]) -> Vec<(Severity, LintRule, &str)> {
+    warnings
+        .iter()
+        .map(|warning| (warning.severity(), warning.rule(), warning.path()))
+        .collect()
+}
+
+#[test]
+fn bundled_datafile() {
+    let content = std::fs::read_to_string(FILE_PATH).unwrap();
+    let warnings = lint::check(&Datafile::build(&content).unwrap());
+
+    // Sorted with the most severe first
+    assert_eq!(
+        summary(&warnings),
+        [
+            (
+                Severity::Warning,
+                LintRule::UndeclaredAttribute,
+                "experiments[\"hero_layout_experiment\"].audienceIds"
+            ),
+            (
+                Severity::Warning,
+                LintRule::UndeclaredAttribute,
+                "experiments[\"hero_layout_experiment\"].audienceIds"
+            ),
+            (
+                Severity::Info,
+                LintRule::VariationWithoutTraffic,
+                "experiments[\"sorting_algorithm_experiment\"].variations[\"off\"]"
+            ),
+            (
+                Severity::Info,
+                LintRule::TrafficAllocationSum,
+                "rollouts[\"rollout-19334-21533480907\"].experiments[\"qa_rollout_targeted_delivery\"].trafficAllocation"
+            ),
+        ]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "warning: experiments[\"hero_layout_experiment\"].audienceIds: \
+         audience `[Web] Desktop Only` uses attribute `isMobile`, which is not declared"
+    );
+}
+
+#[test]
+fn traffic_allocation_sum() {
+    let datafile = DatafileBuilder::new()
+        .flag(
+            FlagBuilder::new("checkout")
+                .experiment(RuleBuilder::new("too_little").variation("on", 4_000))
+                .experiment(
+                    RuleBuilder::new("too_much")
+                        .variation("on", 6_000)
+                        .variation("off", 6_000),
+                )
+                .rollout_rule(RuleBuilder::new("everyone_else").variation("on", 10_000)),
+        )
+        .build()
+        .unwrap();
+
+    let warnings = lint::check(&datafile);
+    assert_eq!(
+        summary(&warnings),
+        [
+            (Severity::Error, LintRule::TrafficAllocationSum, "experiments[\"too_much\"].trafficAllocation"),
+            (Severity::Warning, LintRule::TrafficAllocationSum, "experiments[\"too_little\"].trafficAllocation"),
+        ]
+    );
+    assert_eq!(warnings[0].message(), "traffic allocation adds up to 12000 instead of 10000");
+}
+
+#[test]
+fn variation_without_traffic() {
+    let datafile = DatafileBuilder::new()
+        .flag(
+            FlagBuilder::new("checkout")
+                .experiment(
+                    RuleBuilder::new("checkout_experiment")
+                        .variation("control", 10_000)
+                        .with_variation(VariationBuilder::new("treatment", 0)),
+                )
+                .rollout_rule(RuleBuilder::new("everyone_else").variation("on", 10_000)),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        summary(&lint::check(&datafile)),
+        [(
+            Severity::Info,
+            LintRule::VariationWithoutTraffic,
+            "experiments[\"checkout_experiment\"].variations[\"treatment\"]"
+        )]
+    );
+}
+
+#[test]
+fn undeclared_attribute() {
+    let datafile = DatafileBuilder::new()
+        .attribute("platform")
+        .audience(
+            "mobile web",
+            json!(["and",
+                {"type": "custom_attribute", "match": "exact", "name": "platform", "value": "web"},
+                {"type": "custom_attribute", "match": "exists", "name": "isMobile"}
+            ]),
+        )
+        .flag(
+            FlagBuilder::new("checkout")
+                .experiment(
+                    RuleBuilder::new("checkout_experiment")
+                        .audience("mobile web")
+                        .variation("on", 10_000),
+                )
+                .rollout_rule(RuleBuilder::new("everyone_else").variation("on", 10_000)),
+        )
+        .build()
+        .unwrap();
+
+    let warnings = lint::check(&datafile);
+    assert_eq!(
+        summary(&warnings),
+        [(Severity::Warning, LintRule::UndeclaredAttribute, "experiments[\"checkout_experiment\"].audienceIds")]
+    );
+    assert_eq!(warnings[0].message(), "audience `mobile web` uses attribute `isMobile`, which is not declared");
+}
+
+#[test]
+fn missing_everyone_else_rule() {
+    let datafile = DatafileBuilder::new()
+        .audience(
+            "web",
+            json!(["or", {"type": "custom_attribute", "match": "exact", "name": "platform", "value": "web"}]),
+        )
+        .attribute("platform")
+        .flag(FlagBuilder::new("without_rules"))
+        .flag(
+            FlagBuilder::new("targeted").rollout_rule(
+                RuleBuilder::new("web_only")
+                    .audience("web")
+                    .variation("on", 10_000),
+            ),
+        )
+        .build()
+        .unwrap();
+
+    let warnings = lint::check(&datafile);
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings
+        .iter()
+        .all(|warning| warning.rule() == LintRule::MissingEveryoneElseRule && warning.severity() == Severity::Warning));
+}
+
+#[test]
+fn dangling_reference() {
+    let content = std::fs::read_to_string(FILE_PATH).unwrap();
+    let mut datafile: serde_json::Value = serde_json::from_str(&content).unwrap();
+    datafile["experiments"][0]["audienceConditions"] = json!(["or", "18423300742", "404"]);
+    datafile["rollouts"][0]["experiments"][0]["audienceIds"] = json!(["405"]);
+    datafile["events"][0]["experimentIds"] = json!(["9300000000001"]);
+    datafile["featureFlags"][1]["experimentIds"] = json!(["9300000127039", "9300000000000"]);
+
+    // Missing audiences and experiments do not stop the datafile from working
+    let datafile = Datafile::build(&datafile.to_string()).unwrap();
+    let warnings = lint::check(&datafile)
+        .into_iter()
+        .filter(|warning| warning.rule() == LintRule::DanglingReference)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        summary(&warnings),
+        [
+            (Severity::Warning, LintRule::DanglingReference, "events[\"subscribe\"].experimentIds"),
+            (
+                Severity::Warning,
+                LintRule::DanglingReference,
+                "experiments[\"buy_button_experiment\"].audienceConditions"
+            ),
+            (Severity::Warning, LintRule::DanglingReference, "featureFlags[\"buy_button\"].experimentIds"),
+            (
+                Severity::Warning,
+                LintRule::DanglingReference,
+                "rollouts[\"rollout-19334-21533480907\"].experiments[\"qa_rollout_targeted_delivery\"].audienceIds"
+            ),
+        ]
+    );
+    assert_eq!(warnings[1].message(), "refers to audience `404`, which does not exist");
+}
+
+#[test]
+fn serialized_warning() {
+    let datafile = DatafileBuilder::new()
+        .flag(FlagBuilder::new("without_rules"))
+        .build()
+        .unwrap();
+
+    let json = serde_json::to_value(lint::check(&datafile)).unwrap();
+    assert_eq!(json[0]["severity"], "warning");
+    assert_eq!(json[0]["rule"], "missingEveryoneElseRule");
+}
@@ -0,0 +1,148 @@
+// External imports
+use serde_json::{json, Value};
+
+// Imports from Optimizely crate
+use optimizely::datafile::Datafile;
+use optimizely::decision::DecideOptions;
+use optimizely::{user_attributes, Client};
+
+// Relative imports of sub modules
+use common::FILE_PATH;
+mod common;
+
+fn bundled_datafile() -> Datafile {
+    let content = std::fs::read_to_string(FILE_PATH).unwrap();
+    Datafile::build(&content).unwrap()
+}
+
+#[test]
+fn round_trip() {
+    let datafile = bundled_datafile();
+    let json = serde_json::to_string(&datafile).unwrap();
+
+    // The serialized datafile can be built again, and serializes to the exact same JSON
+    let rebuilt = Datafile::build(&json).unwrap();
+    assert_eq!(serde_json::to_string(&rebuilt).unwrap(), json);
+
+    // Both datafiles describe the same configuration
+    let original_client = Client::from_local_datafile(FILE_PATH).unwrap().initialize();
+    let rebuilt_client = Client::from_string(&json).unwrap().initialize();
+    assert_eq!(
+        serde_json::to_value(original_client.optimizely_config()).unwrap(),
+        serde_json::to_value(rebuilt_client.optimizely_config()).unwrap()
+    );
+
+    // And make the same decisions
+    let decide_options = DecideOptions {
+        disable_decision_event: true,
+        ..DecideOptions::default()
+    };
+    for user_id in ["user1", "user2", "user3", "user4", "user5"] {
+        let attributes = user_attributes! { "isMobile" => false, "platform" => "web" };
+        let original = original_client.create_user_context_with_attributes(user_id, attributes.clone());
+        let rebuilt = rebuilt_client.create_user_context_with_attributes(user_id, attributes);
+        for flag_key in [
+            "qa_rollout",
+            "buy_button",
+            "hero_layout",
+            "sorting_algorithm",
+            "header_text",
+        ] {
+            let original_decision = original.decide_with_options(flag_key, &decide_options);
+            let rebuilt_decision = rebuilt.decide_with_options(flag_key, &decide_options);
+            assert_eq!(original_decision.variation_key(), rebuilt_decision.variation_key());
+            assert_eq!(original_decision.variables(), rebuilt_decision.variables());
+        }
+    }
+}
+
+#[test]
+fn serialized_structure() {
+    let json = serde_json::to_value(bundled_datafile()).unwrap();
+
+    assert_eq!(json["version"], "4");
+    assert_eq!(json["revision"], "73");
+    assert_eq!(json["accountId"], "21537940595");
+
+    // Maps are written back as arrays, sorted by their key
+    let flag_keys = json["featureFlags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|flag| flag["key"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        flag_keys,
+        [
+            "buy_button",
+            "header_text",
+            "hero_layout",
+            "qa_rollout",
+            "simplified_checkout",
+            "sorting_algorithm"
+        ]
+    );
+
+    // Traffic allocations are ordered by the end of their range
+    let experiment = json["experiments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|experiment| experiment["key"] == "buy_button_experiment")
+        .unwrap();
+    assert_eq!(experiment["layerId"], "9300000093600");
+    assert_eq!(experiment["trafficAllocation"][0], json!({"entityId": "87755", "endOfRange": 2500}));
+
+    // Audience conditions are written as JSON instead of a string
+    let audience = &json["typedAudiences"][2];
+    assert_eq!(audience["id"], "18423300742");
+    assert!(audience["conditions"].is_array());
+    assert_eq!(json["audiences"], json!([]));
+}
+
+#[test]
+fn legacy_audiences_are_typed() {
+    let content = std::fs::read_to_string(FILE_PATH).unwrap();
+    let mut datafile: Value = serde_json::from_str(&content).unwrap();
+    datafile["version"] = json!("3");
+    datafile["audiences"] = json!([{
+        "id": "500",
+        "name": "legacy",
+        "conditions": "[\"or\", {\"name\": \"platform\", \"type\": \"custom_attribute\", \"value\": \"web\"}]"
+    }]);
+
+    let datafile = Datafile::build(&datafile.to_string()).unwrap();
+    let json = serde_json::to_value(&datafile).unwrap();
+
+    // Written as a datafile of the latest version, with the legacy audience as typed audience
+    assert_eq!(json["version"], "4");
+    let audience = json["typedAudiences"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|audience| audience["id"] == "500")
+        .unwrap();
+    assert_eq!(
+        audience["conditions"],
+        json!(["or", {"type": "custom_attribute", "match": "exact", "name": "platform", "value": "web"}])
+    );
+}
+
+#[test]
+fn unsupported_match_type_is_kept() {
+    let content = std::fs::read_to_string(FILE_PATH).unwrap();
+    let mut datafile: Value = serde_json::from_str(&content).unwrap();
+    let condition = json!({"type": "custom_attribute", "match": "semver_ge", "name": "app_version", "value": "1.2.0"});
+    datafile["typedAudiences"][0]["conditions"] = json!(["and", ["or", condition]]);
+
+    // The condition is written back exactly as it was, even though it can not be evaluated
+    let datafile = Datafile::build(&datafile.to_string()).unwrap();
+    let json = serde_json::to_value(&datafile).unwrap();
+    let audience = json["typedAudiences"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|audience| audience["conditions"][1][1]["match"] == "semver_ge")
+        .unwrap();
+    assert_eq!(audience["conditions"], json!(["and", ["or", condition]]));
+}
@@ -0,0 +1,159 @@
+// External imports
+use serde_json::{json, Value};
+
+// Imports from Optimizely crate
+use optimizely::datafile::{Datafile, DatafileError};
+use optimizely::{user_attributes, Client};
+
+// Relative imports of sub modules
+use common::{setup, setup_with_datafile, TestContext};
+mod common;
+
+// Move the typed audiences into the legacy audiences array, with the conditions as a string
+fn convert_to_legacy_audiences(datafile: &mut Value) {
+    let typed_audiences = datafile
+        .as_object_mut()
+        .unwrap()
+        .remove("typedAudiences")
+        .unwrap();
+
+    let legacy_audiences = datafile["audiences"].as_array_mut().unwrap();
+    for mut audience in typed_audiences.as_array().unwrap().iter().cloned() {
+        audience["conditions"] = Value::from(audience["conditions"].to_string());
+        legacy_audiences.push(audience);
+    }
+}
+
+// Variation keys of every flag for users with different attributes
+fn decisions(ctx: &TestContext) -> Vec<String> {
+    let users = [
+        ("user1", user_attributes! { "isMobile" => true, "platform" => "web" }),
+        ("user2", user_attributes! { "isMobile" => false, "platform" => "web" }),
+        ("user3", user_attributes! { "isMobile" => false, "platform" => "ios" }),
+        ("user4", user_attributes! {}),
+    ];
+    let flag_keys = [
+        "qa_rollout",
+        "buy_button",
+        "hero_layout",
+        "sorting_algorithm",
+        "header_text",
+    ];
+
+    users
+        .into_iter()
+        .flat_map(|(user_id, attributes)| {
+            let user_context = ctx
+                .client
+                .create_user_context_with_attributes(user_id, attributes);
+            flag_keys
+                .iter()
+                .map(|flag_key| user_context.decide(flag_key).variation_key().to_owned())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[test]
+fn legacy_audiences_only() {
+    let expected = decisions(&setup());
+
+    // Version 3 datafiles do not have typed audiences
+    let ctx = setup_with_datafile(|datafile| {
+        datafile["version"] = json!("3");
+        convert_to_legacy_audiences(datafile);
+    });
+
+    assert_eq!(decisions(&ctx), expected);
+}
+
+#[test]
+fn typed_audiences_take_precedence() {
+    let ctx = setup_with_datafile(|datafile| {
+        datafile["audiences"] = json!([
+            {
+                "id": "18396710504",
+                "name": "legacy optimizely",
+                "conditions": "[\"or\", {\"name\": \"platform\", \"type\": \"custom_attribute\", \"value\": \"never\"}]"
+            },
+            {
+                "id": "500",
+                "name": "legacy only",
+                "conditions": "[\"or\", {\"name\": \"platform\", \"type\": \"custom_attribute\", \"value\": \"web\"}]"
+            }
+        ]);
+    });
+
+    let config = ctx.client.optimizely_config();
+    let audience = |audience_id: &str| {
+        config
+            .audiences
+            .iter()
+            .find(|audience| audience.id == audience_id)
+            .unwrap()
+    };
+
+    assert_eq!(audience("18396710504").name, "optimizely");
+
+    // Legacy conditions without a match type use exact matching
+    assert_eq!(audience("500").name, "legacy only");
+    assert_eq!(
+        audience("500").conditions,
+        r#"["or",{"type":"custom_attribute","match":"exact","name":"platform","value":"web"}]"#
+    );
+}
+
+#[test]
+fn dummy_audience_is_hidden() {
+    let client = Client::from_local_datafile(common::FILE_PATH)
+        .unwrap()
+        .initialize();
+
+    let config = client.optimizely_config();
+    assert_eq!(config.audiences.len(), 3);
+    assert!(client.datafile().audience("$opt_dummy_audience").is_some());
+}
+
+#[test]
+fn version_2_without_flags() {
+    let content = r#"
+    {
+        "version": "2",
+        "accountId": "21537940595",
+        "projectId": "21537940595",
+        "revision": "12",
+        "attributes": [{"id": "100", "key": "platform"}],
+        "audiences": [
+            {
+                "id": "200",
+                "name": "web",
+                "conditions": "[\"and\", [\"or\", {\"name\": \"platform\", \"type\": \"custom_attribute\", \"value\": \"web\"}]]"
+            }
+        ],
+        "events": [{"id": "300", "key": "purchase", "experimentIds": ["400"]}],
+        "experiments": [
+            {
+                "id": "400",
+                "key": "checkout",
+                "layerId": "401",
+                "audienceIds": ["200"],
+                "trafficAllocation": [{"entityId": "402", "endOfRange": 10000}],
+                "variations": [{"id": "402", "key": "treatment"}]
+            }
+        ]
+    }"#;
+
+    let datafile = Datafile::build(content).unwrap();
+    assert_eq!(datafile.revision(), 12);
+    assert!(datafile.audience("200").is_some());
+    assert!(datafile.experiment("400").is_some());
+}
+
+#[test]
+fn unsupported_versions() {
+    for version in ["1", "5", "four"] {
+        let content = format!(r#"{{"version": "{version}"}}"#);
+        let report = Datafile::build(&content).err().unwrap();
+        assert_eq!(report.current_context(), &DatafileError::UnsupportedVersion(version.to_owned()));
+    }
+}
@@ -0,0 +1,164 @@
+// External imports
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Imports from Optimizely crate
+use optimizely::notification::{Notification, NotificationType};
+use optimizely::Client;
+
+// Relative imports of sub modules
+use common::test_support::temp_path;
+use common::{FILE_PATH, REVISION};
+mod common;
+
+// Copy of the bundled datafile in the temporary directory, unique for each test
+fn copy_datafile(name: &str) -> PathBuf {
+    let path = temp_path("datafile", name);
+    std::fs::copy(FILE_PATH, &path).unwrap();
+    path
+}
+
+// Write the bundled datafile with another revision and a modification
+fn write_datafile<F>(path: &PathBuf, revision: u32, modify: F)
+where
+    F: FnOnce(&mut Value),
+{
+    let content = std::fs::read_to_string(FILE_PATH).unwrap();
+    let mut datafile: Value = serde_json::from_str(&content).unwrap();
+    datafile["revision"] = json!(revision.to_string());
+    modify(&mut datafile);
+    std::fs::write(path, datafile.to_string()).unwrap();
+}
+
+fn wait_until<F: Fn() -> bool>(condition: F) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() {
+        assert!(Instant::now() < deadline, "datafile was not reloaded");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn wait_for_revision(client: &Client, revision: u32) {
+    wait_until(|| client.datafile().revision() == revision);
+}
+
+#[test]
+fn reload_on_change() {
+    let path = copy_datafile("reload");
+    let client = Client::from_local_datafile(path.to_str().unwrap())
+        .unwrap()
+        .with_file_watching(Duration::from_millis(10))
+        .initialize();
+
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let listener_updates = Arc::clone(&updates);
+    client
+        .notification_center()
+        .add_listener(NotificationType::OptimizelyConfigUpdate, move |notification| {
+            if let Notification::OptimizelyConfigUpdate(update) = notification {
+                listener_updates.lock().unwrap().push((
+                    update.old_revision,
+                    update.new_revision,
+                    update.diff.flags.removed.clone(),
+                ));
+            }
+        });
+
+    write_datafile(&path, REVISION + 1, |datafile| {
+        datafile["featureFlags"].as_array_mut().unwrap().remove(5);
+    });
+    wait_for_revision(&client, REVISION + 1);
+    assert!(client.datafile().flag("simplified_checkout").is_none());
+
+    // The last valid datafile is kept when the file is invalid
+    std::fs::write(&path, r#"{"version": "4", "revision": "#).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(client.datafile().revision(), REVISION + 1);
+
+    // And replaced again once the file is fixed
+    write_datafile(&path, REVISION + 2, |_| {});
+    wait_for_revision(&client, REVISION + 2);
+
+    // Listeners are notified right after the datafile is replaced
+    wait_until(|| updates.lock().unwrap().len() == 2);
+
+    assert_eq!(
+        *updates.lock().unwrap(),
+        [
+            (REVISION, REVISION + 1, vec![String::from("simplified_checkout")]),
+            (REVISION + 1, REVISION + 2, vec![]),
+        ]
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn replaced_file() {
+    let path = copy_datafile("replaced");
+    let client = Client::from_local_datafile(path.to_str().unwrap())
+        .unwrap()
+        .with_file_watching(Duration::from_millis(10))
+        .initialize();
+
+    // Writing to another file and renaming it over the datafile is picked up as well
+    let staging = path.with_extension("staging");
+    write_datafile(&staging, REVISION + 1, |_| {});
+    std::fs::rename(&staging, &path).unwrap();
+    wait_for_revision(&client, REVISION + 1);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn without_watching() {
+    let path = copy_datafile("without");
+    let client = Client::from_local_datafile(path.to_str().unwrap())
+        .unwrap()
+        .initialize();
+
+    // The file is only read once
+    write_datafile(&path, REVISION + 1, |_| {});
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(client.datafile().revision(), REVISION);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn no_reload_after_close() {
+    let path = copy_datafile("close");
+    let client = Client::from_local_datafile(path.to_str().unwrap())
+        .unwrap()
+        .with_file_watching(Duration::from_millis(10))
+        .initialize();
+
+    // Closing stops the thread that watches the file
+    client.close(Duration::from_secs(1));
+    write_datafile(&path, REVISION + 1, |_| {});
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(client.datafile().revision(), REVISION);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn reload_without_new_revision() {
+    let path = copy_datafile("same-revision");
+    let client = Client::from_local_datafile(path.to_str().unwrap())
+        .unwrap()
+        .with_file_watching(Duration::from_millis(10))
+        .initialize();
+
+    // An edit that did not change the revision is used as well
+    write_datafile(&path, REVISION, |datafile| {
+        datafile["featureFlags"].as_array_mut().unwrap().remove(5);
+    });
+    wait_until(|| client.datafile().flag("simplified_checkout").is_none());
+    assert_eq!(client.datafile().revision(), REVISION);
+
+    std::fs::remove_file(path).unwrap();
+}
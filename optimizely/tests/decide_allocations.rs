@@ -0,0 +1,83 @@
+// External imports
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Imports from Optimizely crate
+use optimizely::decision::DecideOptions;
+use optimizely::user_attributes;
+
+// Relative imports of sub modules
+use common::setup;
+mod common;
+
+// Allocator that counts the allocations of each thread, so tests running in parallel do not interfere
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Number of allocations made by the current thread while running `f`
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn decide_does_not_allocate() {
+    let ctx = setup();
+    let user_context = ctx
+        .client
+        .create_user_context_with_attributes("user3", user_attributes! { "is_employee" => true });
+    let options = DecideOptions {
+        disable_decision_event: true,
+        ..DecideOptions::default()
+    };
+
+    // An experiment, a rollout with an audience, a flag with variables and a user that does not qualify for a rollout
+    for (flag_key, variation_key) in [
+        ("buy_button", "primary"),
+        ("qa_rollout", "on"),
+        ("sorting_algorithm", "variation_2"),
+        ("simplified_checkout", "off"),
+    ] {
+        // Shared values for decisions without a variation are created by the first decision
+        user_context.decide_with_options(flag_key, &options);
+
+        let allocations = allocations(|| {
+            let decision = user_context.decide_with_options(flag_key, &options);
+            assert_eq!(decision.variation_key(), variation_key);
+        });
+        assert_eq!(allocations, 0, "deciding {flag_key} allocated");
+    }
+}
+
+#[test]
+fn decide_copies_attributes_on_write() {
+    let ctx = setup();
+    let mut user_context = ctx
+        .client
+        .create_user_context_with_attributes("user3", user_attributes! { "is_employee" => true });
+
+    // The decision shares the attributes, until the user context changes them
+    let decision = user_context.decide("qa_rollout");
+    user_context.set_attribute("is_employee", false);
+
+    assert_eq!(decision.user_context().attributes(), &user_attributes! { "is_employee" => true });
+    assert_eq!(user_context.attributes(), &user_attributes! { "is_employee" => false });
+}
@@ -0,0 +1,110 @@
+#![cfg(feature = "batch")]
+
+// External imports
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// Imports from Optimizely crate
+use optimizely::client::BatchOptions;
+use optimizely::user_attributes;
+
+// Relative imports of sub modules
+use common::setup;
+mod common;
+
+#[test]
+fn decide_batch_matches_single_decisions() {
+    let ctx = setup();
+    let client = Arc::new(ctx.client);
+
+    let users = (0..1_000).map(|i| (format!("user{i}"), user_attributes! { "index" => i }));
+    let decisions = client
+        .decide_batch("buy_button", users, BatchOptions::default())
+        .collect::<Vec<_>>();
+
+    // Every user is decided exactly once, in any order
+    assert_eq!(decisions.len(), 1_000);
+    let user_ids = decisions
+        .iter()
+        .map(|decision| decision.user_context().user_id())
+        .collect::<HashSet<_>>();
+    assert_eq!(user_ids.len(), 1_000);
+
+    // Each decision is the same as deciding for a single user
+    for decision in &decisions {
+        let user_context = decision.user_context();
+        let single_decision = client
+            .create_user_context_with_attributes(user_context.user_id(), user_context.attributes().clone())
+            .decide("buy_button");
+        assert_eq!(decision, &single_decision);
+    }
+
+    // Only the single decisions above have sent events
+    assert_eq!(ctx.event_list.lock().unwrap().len(), 1_000);
+}
+
+#[test]
+fn decide_batch_sends_events_when_asked() {
+    let ctx = setup();
+    let client = Arc::new(ctx.client);
+
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    let options = BatchOptions {
+        send_decision_events: true,
+        thread_pool: Some(Arc::new(thread_pool)),
+        buffer_size: 1,
+        ..BatchOptions::default()
+    };
+    let users = (0..100).map(|i| (format!("user{i}"), user_attributes! {}));
+    let count = client.decide_batch("buy_button", users, options).count();
+
+    assert_eq!(count, 100);
+    assert_eq!(ctx.event_list.lock().unwrap().len(), 100);
+}
+
+#[test]
+fn decide_batch_stops_when_dropped() {
+    let ctx = setup();
+    let client = Arc::new(ctx.client);
+
+    // Count how many users are read from an endless stream of users
+    let read_count = Arc::new(AtomicUsize::new(0));
+    let users = {
+        let read_count = Arc::clone(&read_count);
+        (0..).map(move |i| {
+            read_count.fetch_add(1, Ordering::SeqCst);
+            (format!("user{i}"), user_attributes! {})
+        })
+    };
+
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    let options = BatchOptions {
+        thread_pool: Some(Arc::new(thread_pool)),
+        buffer_size: 8,
+        ..BatchOptions::default()
+    };
+
+    let count = client
+        .decide_batch("qa_rollout", users, options)
+        .take(10)
+        .count();
+    assert_eq!(count, 10);
+
+    // Once the decisions are dropped, the threads that are still deciding notice it and stop reading users
+    thread::sleep(Duration::from_millis(100));
+    let read_after_drop = read_count.load(Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(read_count.load(Ordering::SeqCst), read_after_drop);
+
+    // At most the consumed decisions, a full buffer and one user per thread are read
+    assert!(read_after_drop <= 10 + 8 + 2, "read {read_after_drop} users");
+}
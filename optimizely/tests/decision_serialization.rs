@@ -0,0 +1,132 @@
+// External imports
+use serde_json::json;
+
+// Imports from Optimizely crate
+use optimizely::decision::{DecideOptions, Decision};
+
+// Relative imports of sub modules
+use common::setup;
+mod common;
+
+#[test]
+fn decision_json_shape() {
+    let ctx = setup();
+    let attributes = optimizely::user_attributes! {
+        "is_employee" => true,
+    };
+    let user_context = ctx
+        .client
+        .create_user_context_with_attributes("user7", attributes);
+    let decision = user_context.decide("sorting_algorithm");
+
+    let expected = json!({
+        "flagKey": "sorting_algorithm",
+        "enabled": true,
+        "variationKey": "variation_1",
+        "ruleKey": "sorting_algorithm_experiment",
+        "variables": {
+            "direction": "desc",
+            "field": "price",
+            "number_of_products": 4
+        },
+        "reasons": [],
+        "userContext": {
+            "userId": "user7",
+            "attributes": {
+                "is_employee": true
+            }
+        }
+    });
+    assert_eq!(serde_json::to_value(&decision).unwrap(), expected);
+}
+
+#[test]
+fn decision_round_trip() {
+    let ctx = setup();
+    let mut user_context = ctx.client.create_user_context("user1");
+    user_context.set_attribute("app_version", "1.3.2");
+    user_context.set_attribute("visits", 12);
+    user_context.set_attribute("ratio", 0.5);
+
+    let options = DecideOptions {
+        include_reasons: true,
+        ..DecideOptions::default()
+    };
+    let decision = user_context.decide_with_options("hero_layout", &options);
+
+    let json = serde_json::to_string(&decision).unwrap();
+    let restored: Decision<'static> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored, decision);
+    assert_eq!(restored.user_context().attributes(), user_context.attributes());
+}
+
+#[test]
+fn decision_with_unsupported_attribute() {
+    let ctx = setup();
+    let decision = ctx.client.create_user_context("user1").decide("buy_button");
+
+    // A stored decision with an unsupported attribute value is still restored, without that attribute
+    let mut json = serde_json::to_value(&decision).unwrap();
+    json["userContext"]["attributes"] = json!({ "is_employee": true, "scores": [1, 2] });
+    let restored: Decision<'static> = serde_json::from_value(json).unwrap();
+
+    assert_eq!(restored.variation_key(), decision.variation_key());
+    assert_eq!(restored.user_context().attributes(), &optimizely::user_attributes! { "is_employee" => true });
+}
+
+#[test]
+fn variables_default_when_feature_disabled() {
+    let ctx = setup();
+    let decision = ctx
+        .client
+        .create_user_context("user1")
+        .decide("hero_layout");
+
+    // The "off" variation overrides the variable, but the feature is disabled so the default is used
+    assert!(!decision.enabled());
+    assert_eq!(decision.variables()["path"], json!("/index.html"));
+}
+
+#[test]
+fn exclude_variables() {
+    let ctx = setup();
+    let options = DecideOptions {
+        exclude_variables: true,
+        ..DecideOptions::default()
+    };
+    let decision = ctx
+        .client
+        .create_user_context("user7")
+        .decide_with_options("sorting_algorithm", &options);
+
+    assert!(decision.variables().is_empty());
+}
+
+#[test]
+fn reasons() {
+    let ctx = setup();
+    let user_context = ctx.client.create_user_context("user1");
+
+    // Reasons are only collected when asked for
+    let decision = user_context.decide("hero_layout");
+    assert!(decision.reasons().is_empty());
+
+    let options = DecideOptions {
+        include_reasons: true,
+        ..DecideOptions::default()
+    };
+    let decision = user_context.decide_with_options("hero_layout", &options);
+    assert_eq!(
+        decision.reasons(),
+        [
+            "User \"user1\" does not meet conditions for experiment \"hero_layout_experiment\".",
+            "User \"user1\" bucketed into targeting rule \"default-rollout-28662-21533480907\"."
+        ]
+    );
+
+    // Critical errors are always included
+    let decision = user_context.decide("does_not_exist");
+    assert_eq!(decision.reasons(), ["No flag was found for key \"does_not_exist\"."]);
+    assert_eq!(decision.rule_key(), None);
+}
@@ -1,11 +1,18 @@
+// Test names use a double underscore to group cases of the same feature
+#![allow(non_snake_case)]
+
 // Imports from Optimizely crate
 use optimizely::{
     datafile::{AudienceCondition, BooleanCondition},
     user_attributes,
 };
+#[cfg(feature = "online")]
+use optimizely::{decision::RuleType, event_api::Event};
 
 // Relative imports of sub modules
 use common::setup;
+#[cfg(feature = "online")]
+use common::setup_with_datafile;
 mod common;
 
 macro_rules! assert_decision {
@@ -46,7 +53,7 @@ fn qa_rollout_flag() {
     assert_decision!(ctx, flag_key, "user15", true, "on");
 
     // Since this key is a rollout, no events should be dispatched
-    assert_eq!(ctx.event_list.borrow().len(), 0);
+    assert_eq!(ctx.event_list.lock().unwrap().len(), 0);
 }
 
 #[test]
@@ -89,7 +96,87 @@ fn buy_button_flag() {
     assert_decision!(ctx, flag_key, "user31", true, "primary");
 
     // Each of those 32 users should dispatch an event
-    assert_eq!(ctx.event_list.borrow().len(), 32);
+    assert_eq!(ctx.event_list.lock().unwrap().len(), 32);
+}
+
+#[test]
+#[cfg(feature = "online")]
+fn buy_button_flag_event_metadata() {
+    let ctx = setup();
+    let flag_key = "buy_button";
+
+    assert_decision!(ctx, flag_key, "user0", true, "primary");
+
+    // The decision event should include the flag decision metadata
+    let events = ctx.event_list.lock().unwrap();
+    match &events[0] {
+        Event::Decision {
+            campaign_id,
+            experiment_id,
+            variation_id,
+            metadata,
+            ..
+        } => {
+            assert_eq!(campaign_id, "9300000093600");
+            assert_eq!(experiment_id, "9300000127039");
+            assert_eq!(variation_id, "87755");
+            assert_eq!(metadata.flag_key, "buy_button");
+            assert_eq!(metadata.rule_key, "buy_button_experiment");
+            assert_eq!(metadata.rule_type, RuleType::FeatureTest);
+            assert_eq!(metadata.variation_key, "primary");
+            assert!(metadata.enabled);
+        }
+        _ => panic!("Expected a decision event"),
+    }
+}
+
+#[test]
+#[cfg(feature = "online")]
+fn qa_rollout_flag_with_send_flag_decisions() {
+    let ctx = setup_with_datafile(|datafile| datafile["sendFlagDecisions"] = true.into());
+    let flag_key = "qa_rollout";
+
+    assert_decision!(ctx, flag_key, "user0", false, "off");
+    assert_decision!(ctx, flag_key, "user3", true, "on");
+
+    // Rollouts dispatch events now, also when the flag is off
+    let events = ctx.event_list.lock().unwrap();
+    assert_eq!(events.len(), 2);
+
+    let metadata = events
+        .iter()
+        .map(|event| {
+            event
+                .decision_metadata()
+                .expect("Expected a decision event")
+        })
+        .collect::<Vec<_>>();
+
+    // User0 falls in the "Everyone Else" rule, which is off
+    assert_eq!(metadata[0].flag_key, "qa_rollout");
+    assert_eq!(metadata[0].rule_key, "default-rollout-19334-21533480907");
+    assert_eq!(metadata[0].rule_type, RuleType::Rollout);
+    assert_eq!(metadata[0].variation_key, "off");
+    assert!(!metadata[0].enabled);
+
+    // User3 falls in the targeted delivery
+    assert_eq!(metadata[1].rule_key, "qa_rollout_targeted_delivery");
+    assert_eq!(metadata[1].variation_key, "on");
+    assert!(metadata[1].enabled);
+}
+
+#[test]
+#[cfg(feature = "online")]
+fn invalid_flag_with_send_flag_decisions() {
+    let ctx = setup_with_datafile(|datafile| datafile["sendFlagDecisions"] = true.into());
+
+    // Flags that do not exist never dispatch events
+    let decision = ctx
+        .client
+        .create_user_context("user1")
+        .decide("this_flag_does_not_exist");
+    assert_eq!(decision.variation_key(), "off");
+    assert_eq!(ctx.event_list.lock().unwrap().len(), 0);
 }
 
 #[test]
@@ -104,7 +191,7 @@ fn invalid_flag() {
     assert_decision!(ctx, flag_key, "user4", false, "off");
 
     // Since this key does not exist, no events should be dispatched
-    assert_eq!(ctx.event_list.borrow().len(), 0);
+    assert_eq!(ctx.event_list.lock().unwrap().len(), 0);
 }
 
 #[test]
@@ -144,7 +231,8 @@ fn audience_evaluation() {
         },
     );
 
-    let web_desktop_only_experiment = ctx.client.datafile().experiment("9300000125242").unwrap();
+    let datafile = ctx.client.datafile();
+    let web_desktop_only_experiment = datafile.experiment("9300000125242").unwrap();
 
     assert!(!mweb_user_context.is_in_audience_of(web_desktop_only_experiment));
     assert!(mweb_user_context
@@ -284,3 +372,23 @@ fn audience_evaluation__combinations() {
     assert!(empty_and.evaluate(&|condition| condition.evaluate(&user_attributes! {"age"=>3,"isMobile"=>false})));
     assert!(empty_and.evaluate(&|condition| condition.evaluate(&user_attributes! {"age"=>2,"isMobile"=>false})));
 }
+
+#[test]
+fn audience_evaluation_list_attributes() {
+    let exact: BooleanCondition<AudienceCondition> = serde_json::from_str(
+        "[\"and\", {\"type\":\"custom_attribute\",\"match\":\"exact\",\"name\":\"segments\",\"value\":\"beta\"}]",
+    )
+    .unwrap();
+    assert!(exact.evaluate(&|condition| condition.evaluate(&user_attributes! {"segments"=>vec!["alpha", "beta"]})));
+    assert!(!exact.evaluate(&|condition| condition.evaluate(&user_attributes! {"segments"=>vec!["alphabeta"]})));
+    assert!(!exact.evaluate(&|condition| condition.evaluate(&user_attributes! {"segments"=>Vec::<String>::new()})));
+
+    let substring: BooleanCondition<AudienceCondition> = serde_json::from_str(
+        "[\"and\", {\"type\":\"custom_attribute\",\"match\":\"substring\",\"name\":\"segments\",\"value\":\"beta\"}]",
+    )
+    .unwrap();
+    assert!(substring.evaluate(&|condition| condition.evaluate(&user_attributes! {"segments"=>vec!["alphabeta"]})));
+    assert!(
+        !substring.evaluate(&|condition| condition.evaluate(&user_attributes! {"segments"=>vec!["alpha", "gamma"]}))
+    );
+}
@@ -0,0 +1,149 @@
+// External imports
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Imports from Optimizely crate
+#[cfg(feature = "prometheus")]
+use optimizely::event_api::DispatcherStats;
+use optimizely::event_api::{BatchedEventDispatcher, Event, EventApiClient, EventDispatcher, OverflowPolicy};
+use optimizely::Client;
+
+// Relative imports of sub modules
+use common::test_support::{serve, unreachable_url};
+use common::{setup, ACCOUNT_ID, FILE_PATH};
+mod common;
+
+#[test]
+fn stats_without_tracking() {
+    let ctx = setup();
+    ctx.client.create_user_context("user1").decide("buy_button");
+
+    // The test dispatcher does not keep track of its events
+    assert_eq!(ctx.client.event_dispatcher_stats(), None);
+}
+
+#[test]
+fn stats_of_batched_dispatcher() {
+    let client = Client::from_local_datafile(FILE_PATH)
+        .unwrap()
+        .with_event_dispatcher(BatchedEventDispatcher::default())
+        .initialize();
+
+    for user_id in ["user1", "user2", "user3"] {
+        client.create_user_context(user_id).decide("buy_button");
+    }
+
+    let stats = client.event_dispatcher_stats().unwrap();
+    assert_eq!(stats.events_enqueued, 3);
+    assert_eq!(stats.events_sent + stats.events_dropped + stats.queue_depth, 3);
+
+    // After closing, every event is either sent or dropped
+    client.close(Duration::from_secs(30));
+    let stats = client.event_dispatcher_stats().unwrap();
+    assert_eq!(stats.events_sent + stats.events_dropped, 3);
+    assert_eq!(stats.queue_depth, 0);
+
+    // A single batch was either sent or failed on every attempt
+    match stats.last_success {
+        Some(_) => assert_eq!((stats.batches_sent, stats.events_sent), (1, 3)),
+        None => assert_eq!(stats.http_failures, stats.retries + 1),
+    }
+}
+
+#[test]
+fn dropped_events_are_reported() {
+    let dropped_users = Arc::new(Mutex::new(Vec::new()));
+
+    let listener_users = Arc::clone(&dropped_users);
+    let dispatcher = BatchedEventDispatcher::new(100, OverflowPolicy::DropOldest).with_drop_listener(move |event| {
+        listener_users
+            .lock()
+            .unwrap()
+            .push(event.user_id().to_owned());
+    });
+    dispatcher.close(Duration::from_secs(1));
+
+    // Events sent after closing are dropped right away
+    for user_id in ["user1", "user2"] {
+        dispatcher.send_event(Event::conversion(ACCOUNT_ID, user_id, "22305150298", "purchase"));
+    }
+
+    assert_eq!(*dropped_users.lock().unwrap(), vec!["user1", "user2"]);
+
+    let stats = dispatcher.stats().unwrap();
+    assert_eq!(stats.events_enqueued, 2);
+    assert_eq!(stats.events_dropped, 2);
+    assert_eq!(stats.queue_depth, 0);
+}
+
+// Dispatcher that sends to the given endpoint and keeps the user ids of the events passed to its drop listener
+fn dispatcher_with_dropped_users(endpoint_url: &str) -> (BatchedEventDispatcher, Arc<Mutex<Vec<String>>>) {
+    let dropped_users = Arc::new(Mutex::new(Vec::new()));
+
+    let listener_users = Arc::clone(&dropped_users);
+    let dispatcher = BatchedEventDispatcher::default()
+        .with_api_client(EventApiClient::default().with_endpoint_url(endpoint_url))
+        .with_drop_listener(move |event| {
+            listener_users
+                .lock()
+                .unwrap()
+                .push(event.user_id().to_owned());
+        });
+
+    (dispatcher, dropped_users)
+}
+
+#[test]
+fn events_of_failed_requests_are_reported() {
+    // Every request fails
+    let (dispatcher, dropped_users) = dispatcher_with_dropped_users(&format!("{}/v1/events", unreachable_url()));
+
+    for user_id in ["user1", "user2"] {
+        dispatcher.send_event(Event::conversion(ACCOUNT_ID, user_id, "22305150298", "purchase"));
+    }
+    let report = dispatcher.close(Duration::from_secs(30));
+
+    assert_eq!((report.sent, report.dropped), (0, 2));
+    assert_eq!(*dropped_users.lock().unwrap(), vec!["user1", "user2"]);
+}
+
+#[test]
+fn events_after_close_timeout_are_reported() {
+    // Endpoint that only fails the request after the dispatcher gave up waiting
+    let url = serve(|_| {
+        thread::sleep(Duration::from_millis(500));
+        ("500 Internal Server Error", String::new())
+    });
+    let (dispatcher, dropped_users) = dispatcher_with_dropped_users(&format!("{url}/v1/events"));
+
+    dispatcher.send_event(Event::conversion(ACCOUNT_ID, "user1", "22305150298", "purchase"));
+    let report = dispatcher.close(Duration::from_millis(50));
+    assert_eq!((report.sent, report.dropped), (0, 1));
+
+    // The listener is called by the thread of the dispatcher, once the pending request failed
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while dropped_users.lock().unwrap().is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(*dropped_users.lock().unwrap(), vec!["user1"]);
+}
+
+#[cfg(feature = "prometheus")]
+#[test]
+fn stats_in_prometheus_format() {
+    let stats = DispatcherStats {
+        events_enqueued: 12,
+        events_sent: 10,
+        batches_sent: 1,
+        queue_depth: 2,
+        ..DispatcherStats::default()
+    };
+
+    let text = stats.to_prometheus();
+    assert!(text.contains("# TYPE optimizely_events_enqueued_total counter\noptimizely_events_enqueued_total 12\n"));
+    assert!(text.contains("optimizely_events_sent_total 10\n"));
+    assert!(text.contains("optimizely_batches_sent_total 1\n"));
+    assert!(text.contains("# TYPE optimizely_event_queue_depth gauge\noptimizely_event_queue_depth 2\n"));
+    assert!(text.contains("optimizely_last_success_timestamp_seconds 0\n"));
+}
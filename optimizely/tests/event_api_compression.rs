@@ -0,0 +1,98 @@
+// External imports
+use flate2::read::GzDecoder;
+use serde_json::Value;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+// Imports from Optimizely crate
+#[cfg(feature = "gzip")]
+use optimizely::event_api::BatchedEventDispatcher;
+use optimizely::event_api::{Event, EventApiClient, EventDispatcher, SimpleEventDispatcher};
+
+// Relative imports of sub modules
+use common::test_support::{serve, Request};
+use common::ACCOUNT_ID;
+mod common;
+
+// Decode the body of a request according to its content encoding header
+fn json(request: &Request) -> Value {
+    match request.header("content-encoding") {
+        Some("gzip") => serde_json::from_reader(GzDecoder::new(request.body.as_slice())).unwrap(),
+        None => serde_json::from_slice(&request.body).unwrap(),
+        Some(encoding) => panic!("Unexpected content encoding {encoding}"),
+    }
+}
+
+// Local stand-in for the Event API, which passes every request it receives to the returned receiver
+fn stand_in_endpoint() -> (EventApiClient, Receiver<Request>) {
+    let (transmitter, receiver) = mpsc::channel();
+    let url = serve(move |request| {
+        let _ = transmitter.send(request);
+        ("204 No Content", String::new())
+    });
+
+    (EventApiClient::default().with_endpoint_url(format!("{url}/v1/events")), receiver)
+}
+
+fn conversion_event(user_id: &str) -> Event {
+    Event::conversion(ACCOUNT_ID, user_id, "22305150298", "purchase")
+}
+
+#[test]
+fn uncompressed_by_default() {
+    let (api_client, requests) = stand_in_endpoint();
+    #[cfg(feature = "gzip")]
+    assert!(!api_client.gzip());
+
+    let dispatcher = SimpleEventDispatcher::default().with_api_client(api_client);
+    dispatcher.send_event(conversion_event("user1"));
+
+    let request = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(request.header("content-encoding"), None);
+
+    let json = json(&request);
+    assert_eq!(json["account_id"], ACCOUNT_ID);
+    assert_eq!(json["visitors"][0]["visitor_id"], "user1");
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip_with_simple_dispatcher() {
+    let (api_client, requests) = stand_in_endpoint();
+    let dispatcher = SimpleEventDispatcher::default().with_api_client(api_client.with_gzip(true));
+    dispatcher.send_event(conversion_event("user1"));
+
+    let request = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(request.header("content-encoding"), Some("gzip"));
+
+    let json = json(&request);
+    assert_eq!(json["account_id"], ACCOUNT_ID);
+    assert_eq!(json["visitors"][0]["visitor_id"], "user1");
+    assert_eq!(dispatcher.stats().unwrap().events_sent, 1);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip_with_batched_dispatcher() {
+    let (api_client, requests) = stand_in_endpoint();
+    let dispatcher = BatchedEventDispatcher::default().with_api_client(api_client.with_gzip(true));
+
+    for user_id in ["user1", "user2", "user3"] {
+        dispatcher.send_event(conversion_event(user_id));
+    }
+    let report = dispatcher.close(Duration::from_secs(5));
+    assert_eq!((report.sent, report.dropped), (3, 0));
+
+    // All events are sent in a single compressed request
+    let request = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(request.header("content-encoding"), Some("gzip"));
+    assert!(request.body.starts_with(&[0x1f, 0x8b]));
+
+    let visitor_ids: Vec<_> = json(&request)["visitors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|visitor| visitor["visitor_id"].as_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(visitor_ids, ["user1", "user2", "user3"]);
+}
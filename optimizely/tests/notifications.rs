@@ -0,0 +1,213 @@
+// External imports
+use std::sync::{Arc, Mutex};
+
+// Imports from Optimizely crate
+use optimizely::datafile::Datafile;
+use optimizely::notification::{Notification, NotificationType};
+use optimizely::user_attributes;
+
+// Relative imports of sub modules
+use common::{setup, setup_with_datafile};
+mod common;
+
+#[test]
+fn decision_listener() {
+    let ctx = setup();
+
+    // Store a summary of each decision notification
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let listener_received = Arc::clone(&received);
+    let listener_id = ctx
+        .client
+        .notification_center()
+        .add_listener(NotificationType::Decision, move |notification| {
+            if let Notification::Decision(decision) = notification {
+                let summary = (
+                    decision.user_id.to_owned(),
+                    decision.flag_key.to_owned(),
+                    decision.variation_key.to_owned(),
+                    decision.rule_key.map(String::from),
+                    decision.enabled,
+                    decision.attributes.len(),
+                );
+                listener_received.lock().unwrap().push(summary);
+            }
+        });
+
+    // Decide a rollout, an experiment and a flag that does not exist
+    let user_context = ctx
+        .client
+        .create_user_context_with_attributes("user3", user_attributes! { "is_employee" => true });
+    user_context.decide("qa_rollout");
+    user_context.decide("buy_button");
+    user_context.decide("this_flag_does_not_exist");
+
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec![
+            (
+                "user3".into(),
+                "qa_rollout".into(),
+                "on".into(),
+                Some("qa_rollout_targeted_delivery".into()),
+                true,
+                1
+            ),
+            ("user3".into(), "buy_button".into(), "primary".into(), Some("buy_button_experiment".into()), true, 1),
+            ("user3".into(), "this_flag_does_not_exist".into(), "off".into(), None, false, 1),
+        ]
+    );
+
+    // After removing the listener, no more notifications are received
+    assert!(ctx
+        .client
+        .notification_center()
+        .remove_listener(listener_id));
+    assert!(!ctx
+        .client
+        .notification_center()
+        .remove_listener(listener_id));
+    user_context.decide("qa_rollout");
+    assert_eq!(received.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn decision_listener_for_unknown_flag() {
+    let ctx = setup();
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let listener_received = Arc::clone(&received);
+    ctx.client
+        .notification_center()
+        .add_listener(NotificationType::Decision, move |notification| {
+            if let Notification::Decision(decision) = notification {
+                listener_received.lock().unwrap().push((
+                    decision.flag_key.to_owned(),
+                    decision.enabled,
+                    decision.decision_event_dispatched,
+                ));
+            }
+        });
+
+    // Misspelled or removed flags are still reported to listeners, but never send a decision event
+    ctx.client.create_user_context("user1").decide("buy_buton");
+    assert_eq!(*received.lock().unwrap(), [(String::from("buy_buton"), false, false)]);
+    assert!(ctx.event_list.lock().unwrap().is_empty());
+}
+
+#[test]
+fn listeners_only_receive_their_type() {
+    let ctx = setup();
+
+    // Count the notifications per listener
+    let decisions = Arc::new(Mutex::new(0));
+    let config_updates = Arc::new(Mutex::new(0));
+
+    let counter = Arc::clone(&decisions);
+    ctx.client
+        .notification_center()
+        .add_listener(NotificationType::Decision, move |_| *counter.lock().unwrap() += 1);
+    let counter = Arc::clone(&config_updates);
+    ctx.client
+        .notification_center()
+        .add_listener(NotificationType::OptimizelyConfigUpdate, move |_| *counter.lock().unwrap() += 1);
+
+    let user_context = ctx.client.create_user_context("user1");
+    user_context.decide("buy_button");
+    user_context.decide("qa_rollout");
+
+    assert_eq!(*decisions.lock().unwrap(), 2);
+    assert_eq!(*config_updates.lock().unwrap(), 0);
+
+    // Clearing the listeners stops all notifications
+    ctx.client.notification_center().clear_all_listeners();
+    user_context.decide("buy_button");
+    assert_eq!(*decisions.lock().unwrap(), 2);
+}
+
+#[test]
+#[cfg(feature = "online")]
+fn track_and_log_event_listeners() {
+    let ctx = setup();
+
+    // Store the type of each notification
+    let received = Arc::new(Mutex::new(Vec::new()));
+    for notification_type in [NotificationType::Track, NotificationType::LogEvent] {
+        let listener_received = Arc::clone(&received);
+        ctx.client
+            .notification_center()
+            .add_listener(notification_type, move |notification| {
+                listener_received
+                    .lock()
+                    .unwrap()
+                    .push(notification.notification_type());
+            });
+    }
+
+    let user_context = ctx.client.create_user_context("user1");
+
+    // An experiment sends a decision event
+    user_context.decide("buy_button");
+    assert_eq!(*received.lock().unwrap(), vec![NotificationType::LogEvent]);
+
+    // Tracking sends a conversion event and a track notification
+    user_context.track_event("purchase");
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec![
+            NotificationType::LogEvent,
+            NotificationType::LogEvent,
+            NotificationType::Track
+        ]
+    );
+}
+
+#[test]
+fn panicking_listener() {
+    let ctx = setup();
+
+    // The first listener panics, the second should still be called
+    let called = Arc::new(Mutex::new(false));
+    let listener_called = Arc::clone(&called);
+    ctx.client
+        .notification_center()
+        .add_listener(NotificationType::Decision, |_| panic!("listener failed"));
+    ctx.client
+        .notification_center()
+        .add_listener(NotificationType::Decision, move |_| *listener_called.lock().unwrap() = true);
+
+    let decision = ctx.client.create_user_context("user1").decide("qa_rollout");
+
+    assert_eq!(decision.variation_key(), "off");
+    assert!(*called.lock().unwrap());
+}
+
+#[test]
+fn config_update_listener() {
+    let ctx = setup_with_datafile(|datafile| datafile["revision"] = "80".into());
+
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let listener_updates = Arc::clone(&updates);
+    ctx.client
+        .notification_center()
+        .add_listener(NotificationType::OptimizelyConfigUpdate, move |notification| {
+            if let Notification::OptimizelyConfigUpdate(update) = notification {
+                listener_updates
+                    .lock()
+                    .unwrap()
+                    .push((update.old_revision, update.new_revision));
+            }
+        });
+
+    // Replacing the datafile with another revision notifies the listeners
+    let content = std::fs::read_to_string(common::FILE_PATH).unwrap();
+    let datafile = Datafile::build(&content).unwrap();
+    assert!(ctx.client.update_datafile(datafile));
+    assert_eq!(ctx.client.datafile().revision(), common::REVISION);
+
+    // The same revision again is ignored
+    let datafile = Datafile::build(&content).unwrap();
+    assert!(!ctx.client.update_datafile(datafile));
+
+    assert_eq!(*updates.lock().unwrap(), [(80, common::REVISION)]);
+}
@@ -0,0 +1,76 @@
+// Imports from Optimizely crate
+use optimizely::Client;
+
+// Relative imports of sub modules
+use common::{FILE_PATH, REVISION};
+use serde_json::Value;
+mod common;
+
+#[test]
+fn config_from_fixed_datafile() {
+    let client = Client::from_local_datafile(FILE_PATH)
+        .expect("local datafile should work")
+        .initialize();
+
+    let config = client.optimizely_config();
+
+    // Properties of the environment
+    assert_eq!(config.revision, REVISION);
+    assert_eq!(config.environment_key, "development");
+    assert_eq!(config.sdk_key, "");
+    assert_eq!(config.features_map.len(), 6);
+    assert_eq!(config.experiments_map.len(), 4);
+    assert_eq!(config.attributes.len(), 1);
+    assert_eq!(config.attributes[0].key, "is_employee");
+    assert_eq!(config.events.len(), 2);
+    assert_eq!(config.events[1].key, "subscribe");
+    assert_eq!(config.events[1].experiment_ids.len(), 3);
+
+    // A flag with both an experiment and a rollout
+    let flag = &config.features_map["hero_layout"];
+    assert_eq!(flag.id, "28662");
+    assert_eq!(flag.experiment_rules.len(), 1);
+    assert_eq!(flag.delivery_rules.len(), 1);
+
+    // The experiment has a targeted audience
+    let experiment = &flag.experiment_rules[0];
+    assert_eq!(experiment.key, "hero_layout_experiment");
+    assert_eq!(experiment.audiences, "\"[Web] Desktop Only\"");
+    assert_eq!(experiment.variations_map.len(), 2);
+    assert!(experiment.variations_map["treatment"].feature_enabled);
+
+    // The rollout rule has no audience
+    let rule = &flag.delivery_rules[0];
+    assert_eq!(rule.key, "default-rollout-28662-21533480907");
+    assert_eq!(rule.audiences, "");
+    assert!(!rule.variations_map["off"].feature_enabled);
+
+    // Audience conditions are serialized as the original JSON
+    let audience = config
+        .audiences
+        .iter()
+        .find(|audience| audience.id == "18396710504")
+        .unwrap();
+    assert_eq!(audience.name, "optimizely");
+    assert_eq!(
+        audience.conditions,
+        r#"["and",["or",["or",{"type":"custom_attribute","match":"exists","name":"platform"}]]]"#
+    );
+}
+
+#[test]
+fn config_to_json() {
+    let client = Client::from_local_datafile(FILE_PATH)
+        .expect("local datafile should work")
+        .initialize();
+
+    let json = serde_json::to_value(client.optimizely_config()).unwrap();
+
+    // Keys are camelCase like in the other SDKs
+    assert_eq!(json["environmentKey"], Value::from("development"));
+    assert_eq!(
+        json["featuresMap"]["buy_button"]["experimentRules"][0]["variationsMap"]["primary"]["featureEnabled"],
+        Value::from(true)
+    );
+    assert_eq!(json["experimentsMap"]["buy_button_experiment"]["id"], Value::from("9300000127039"));
+}
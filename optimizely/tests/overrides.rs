@@ -0,0 +1,196 @@
+// External imports
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Imports from Optimizely crate
+use optimizely::client::ClientError;
+use optimizely::decision::{DecideOptions, Decision};
+use optimizely::notification::{Notification, NotificationType};
+use optimizely::Client;
+
+// Relative imports of sub modules
+use common::test_support::temp_path;
+use common::{setup_with_client, FILE_PATH};
+mod common;
+
+// Write an overrides file to the temporary directory, unique for each test
+fn write_overrides(name: &str, content: &serde_json::Value) -> PathBuf {
+    let path = temp_path("overrides", name);
+    std::fs::write(&path, content.to_string()).unwrap();
+    path
+}
+
+#[test]
+fn overridden_variation() {
+    let path = write_overrides(
+        "variation",
+        &json!({ "sorting_algorithm": { "variation": "variation_2", "variables": { "field": "name" } } }),
+    );
+    let ctx = setup_with_client(|client| client.with_overrides_file(path.to_str().unwrap()).unwrap());
+
+    let decision = ctx
+        .client
+        .create_user_context("user1")
+        .decide("sorting_algorithm");
+    assert_eq!(decision.variation_key(), "variation_2");
+    assert!(decision.enabled());
+    assert_eq!(decision.rule_key(), None);
+
+    // Variables that are not overridden come from the variation with the same key
+    assert_eq!(
+        serde_json::to_value(decision.variables()).unwrap(),
+        json!({ "direction": "asc", "field": "name", "number_of_products": 5 })
+    );
+
+    // Overridden flags never send decision events
+    assert!(ctx.event_list.lock().unwrap().is_empty());
+
+    // Other flags are still decided by the datafile
+    let decision = ctx.client.create_user_context("user1").decide("buy_button");
+    assert_eq!(decision.rule_key(), Some("buy_button_experiment"));
+    assert_eq!(ctx.event_list.lock().unwrap().len(), 1);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn overridden_enabled_state() {
+    let path = write_overrides(
+        "enabled",
+        &json!({
+            "qa_rollout": { "enabled": true },
+            "sorting_algorithm": { "enabled": false },
+            "not_in_datafile": { "variables": { "color": "green" } }
+        }),
+    );
+    let ctx = setup_with_client(|client| client.with_overrides_file(path.to_str().unwrap()).unwrap());
+    let user_context = ctx.client.create_user_context("user1");
+
+    let decision = user_context.decide("qa_rollout");
+    assert_eq!((decision.variation_key(), decision.enabled()), ("on", true));
+
+    // Without a variation, variables take their default values
+    let decision = user_context.decide("sorting_algorithm");
+    assert_eq!((decision.variation_key(), decision.enabled()), ("off", false));
+    assert_eq!(decision.variables()["number_of_products"], json!(3));
+
+    // Flags that do not exist yet can be overridden as well
+    let decision = user_context.decide("not_in_datafile");
+    assert_eq!((decision.variation_key(), decision.enabled()), ("on", true));
+    assert_eq!(decision.variables()["color"], json!("green"));
+
+    let options = DecideOptions {
+        include_reasons: true,
+        exclude_variables: true,
+        ..DecideOptions::default()
+    };
+    let decision = user_context.decide_with_options("not_in_datafile", &options);
+    assert!(decision.variables().is_empty());
+    assert_eq!(decision.reasons(), ["Flag \"not_in_datafile\" is overridden by the local overrides file."]);
+
+    assert!(ctx.event_list.lock().unwrap().is_empty());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn decision_notification() {
+    let path = write_overrides("notification", &json!({ "qa_rollout": { "enabled": true } }));
+    let ctx = setup_with_client(|client| client.with_overrides_file(path.to_str().unwrap()).unwrap());
+
+    let notifications = Arc::new(Mutex::new(Vec::new()));
+    let listener_notifications = Arc::clone(&notifications);
+    ctx.client
+        .notification_center()
+        .add_listener(NotificationType::Decision, move |notification| {
+            if let Notification::Decision(decision) = notification {
+                listener_notifications
+                    .lock()
+                    .unwrap()
+                    .push((decision.variation_key.to_owned(), decision.decision_event_dispatched));
+            }
+        });
+
+    ctx.client.create_user_context("user1").decide("qa_rollout");
+    assert_eq!(*notifications.lock().unwrap(), [(String::from("on"), false)]);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn invalid_overrides_file() {
+    let client = Client::from_local_datafile(FILE_PATH).unwrap();
+    let report = client.with_overrides_file("missing.json").err().unwrap();
+    assert_eq!(report.current_context(), &ClientError::InvalidOverrides);
+
+    // Unknown fields are rejected, so a typo does not go unnoticed
+    let path = write_overrides("invalid", &json!({ "qa_rollout": { "enabeld": true } }));
+    let client = Client::from_local_datafile(FILE_PATH).unwrap();
+    let report = client
+        .with_overrides_file(path.to_str().unwrap())
+        .err()
+        .unwrap();
+    assert_eq!(report.current_context(), &ClientError::InvalidOverrides);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn hot_reload() {
+    let path = write_overrides("reload", &json!({ "qa_rollout": { "enabled": true } }));
+    let ctx = setup_with_client(|client| {
+        client
+            .with_overrides_file(path.to_str().unwrap())
+            .unwrap()
+            .with_overrides_reload(Duration::from_millis(10))
+    });
+    let user_context = ctx.client.create_user_context("user1");
+    assert!(user_context.decide("qa_rollout").enabled());
+
+    // Wait until the change is picked up
+    let wait_for = |reloaded: &dyn Fn(&Decision) -> bool| {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !reloaded(&user_context.decide("qa_rollout")) {
+            assert!(Instant::now() < deadline, "overrides were not reloaded");
+            thread::sleep(Duration::from_millis(10));
+        }
+    };
+    std::fs::write(&path, json!({ "qa_rollout": { "enabled": false, "variation": "off" } }).to_string()).unwrap();
+    wait_for(&|decision| !decision.enabled());
+
+    // An invalid file keeps the previous overrides
+    std::fs::write(&path, "{").unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(user_context.decide("qa_rollout").rule_key(), None);
+
+    // Without overrides the datafile is used again
+    std::fs::write(&path, "{}").unwrap();
+    wait_for(&|decision| decision.rule_key().is_some());
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn no_reload_after_close() {
+    let path = write_overrides("close", &json!({ "qa_rollout": { "enabled": true } }));
+    let ctx = setup_with_client(|client| {
+        client
+            .with_overrides_file(path.to_str().unwrap())
+            .unwrap()
+            .with_overrides_reload(Duration::from_millis(10))
+    });
+
+    // Closing stops the thread that reloads the overrides
+    ctx.client.close(Duration::from_secs(1));
+    std::fs::write(&path, json!({ "qa_rollout": { "enabled": false, "variation": "off" } }).to_string()).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert!(ctx
+        .client
+        .create_user_context("user1")
+        .decide("qa_rollout")
+        .enabled());
+
+    std::fs::remove_file(path).unwrap();
+}
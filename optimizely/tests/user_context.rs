@@ -1,5 +1,10 @@
+// External imports
+use std::sync::Arc;
+use std::thread;
+
 // Imports from Optimizely crate
-use optimizely::client::AttributeValue;
+use optimizely::client::{deserialize_attributes, AttributeValue, UserAttributes, UserContext};
+use optimizely::decision::Decision;
 use optimizely::user_attributes;
 
 // Relative imports of sub modules
@@ -94,5 +99,88 @@ fn user_context_track_event() {
     user_context.track_event("purchase");
 
     // Assert that exactly one event is dispatched
-    assert_eq!(ctx.event_list.borrow().len(), 1);
+    assert_eq!(ctx.event_list.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn owned_user_context() {
+    // Owned user contexts and decisions can be shared between threads
+    fn assert_clone_send_sync<T: Clone + Send + Sync>() {}
+    assert_clone_send_sync::<UserContext<'static>>();
+    assert_clone_send_sync::<Decision<'static>>();
+
+    let ctx = setup();
+    let client = Arc::new(ctx.client);
+
+    // Create user context from an owned String that is dropped right after
+    let user_id = String::from("user1");
+    let user_context = client.create_owned_user_context_with_attributes(user_id, user_attributes! { "count" => 3 });
+
+    // Decide in a different thread and return an owned decision
+    let thread_user_context = user_context.clone();
+    let decision = thread::spawn(move || {
+        let flag_key = String::from("buy_button");
+        thread_user_context.decide(&flag_key).into_owned()
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(decision.flag_key(), "buy_button");
+    assert_eq!(decision.variation_key(), "danger");
+    assert_eq!(user_context.user_id(), "user1");
+    assert_eq!(user_context.attributes().len(), 1);
+
+    // The decision event was dispatched from the other thread
+    assert_eq!(ctx.event_list.lock().unwrap().len(), 1);
+
+    // A borrowed user context with the same attributes gives the same decision
+    let borrowed_decision = client
+        .create_user_context_with_attributes("user1", user_attributes! { "count" => 3 })
+        .decide("buy_button");
+    assert_eq!(borrowed_decision, decision);
+}
+
+#[test]
+fn attribute_value_conversions() {
+    assert_eq!(AttributeValue::from(String::from("web")), AttributeValue::String("web".into()));
+    assert_eq!(AttributeValue::from(-3_i64), AttributeValue::Number(Number::from(-3)));
+    assert_eq!(AttributeValue::from(u64::MAX), AttributeValue::Number(Number::from(u64::MAX)));
+    assert_eq!(AttributeValue::from(0.1_f32), AttributeValue::from(0.1_f64));
+    assert_eq!(AttributeValue::from(Some(true)), AttributeValue::Bool(true));
+    assert_eq!(AttributeValue::from(None::<&str>), AttributeValue::Null);
+    assert_eq!(AttributeValue::from(vec!["a", "b"]), AttributeValue::List(vec!["a".into(), "b".into()]));
+}
+
+#[test]
+fn attributes_from_json() {
+    let ctx = setup();
+
+    // Attributes of an incoming request can be deserialized directly
+    let json =
+        r#"{"platform": "web", "age": 31, "ratio": 0.5, "isMobile": false, "referrer": null, "segments": ["a", "b"]}"#;
+    let attributes: UserAttributes = serde_json::from_str(json).unwrap();
+    let user_context = ctx
+        .client
+        .create_user_context_with_attributes("user123", attributes);
+
+    assert_eq!(user_context.attributes()["platform"], AttributeValue::from("web"));
+    assert_eq!(user_context.attributes()["age"], AttributeValue::from(31));
+    assert_eq!(user_context.attributes()["ratio"], AttributeValue::from(0.5));
+    assert_eq!(user_context.attributes()["isMobile"], AttributeValue::from(false));
+    assert_eq!(user_context.attributes()["referrer"], AttributeValue::Null);
+    assert_eq!(user_context.attributes()["segments"], AttributeValue::from(vec!["a", "b"]));
+
+    // Nested objects and lists of other types are rejected
+    assert!(serde_json::from_str::<UserAttributes>(r#"{"nested": {"a": 1}}"#).is_err());
+    assert!(serde_json::from_str::<UserAttributes>(r#"{"list": [1, 2]}"#).is_err());
+}
+
+#[test]
+fn attributes_from_json_skip_unsupported() {
+    // Only the unsupported values are skipped
+    let json = r#"{"platform": "web", "nested": {"a": 1}, "list": [1, 2], "segments": ["a"]}"#;
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let attributes = deserialize_attributes(&mut deserializer).unwrap();
+
+    assert_eq!(attributes, user_attributes! { "platform" => "web", "segments" => vec!["a"] });
 }